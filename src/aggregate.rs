@@ -0,0 +1,288 @@
+use std::collections::BTreeMap;
+
+use crate::job::Job;
+use crate::partition_watcher::Partition;
+
+/// Jobs and resources in use of a single user.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct UserSummary {
+    pub username: String,
+    pub running_jobs: usize,
+    pub pending_jobs: usize,
+    /// Resources of the running jobs.
+    pub total_cpus: u32,
+    pub total_gpus: u32,
+    pub total_mem_mb: u64,
+    /// CPUs times elapsed time of the running jobs.
+    pub cpu_seconds: u64,
+}
+
+/// Sorted by user name.
+pub fn aggregate_by_user(jobs: &[Job]) -> Vec<UserSummary> {
+    let mut users: BTreeMap<&str, UserSummary> = BTreeMap::new();
+    for job in jobs {
+        let summary = users.entry(&job.user).or_insert_with(|| UserSummary {
+            username: job.user.clone(),
+            ..Default::default()
+        });
+        if job.is_pending() {
+            summary.pending_jobs += 1;
+        } else if job.is_running() {
+            summary.running_jobs += 1;
+            if let Some(tres) = job.tres_parsed() {
+                summary.total_cpus = summary.total_cpus.saturating_add(tres.cpu);
+                summary.total_gpus = summary
+                    .total_gpus
+                    .saturating_add(tres.gpu.map_or(0, |g| g.count));
+                summary.total_mem_mb = summary.total_mem_mb.saturating_add(tres.mem_mb);
+                summary.cpu_seconds = summary.cpu_seconds.saturating_add(
+                    (tres.cpu as u64).saturating_mul(job.elapsed_seconds().unwrap_or(0)),
+                );
+            }
+        }
+    }
+    users.into_values().collect()
+}
+
+/// Jobs of a single account and the resources its running jobs used so far.
+#[derive(Debug, Default, PartialEq)]
+pub struct AccountSummary {
+    pub account: String,
+    pub running_jobs: usize,
+    pub pending_jobs: usize,
+    /// CPUs times elapsed time of the running jobs, an estimate as jobs
+    /// don't use all their CPUs all the time.
+    pub cpu_hours_running: f64,
+    pub gpu_hours_running: f64,
+}
+
+/// Sorted by account.
+pub fn aggregate_by_account(jobs: &[Job]) -> Vec<AccountSummary> {
+    let mut accounts: BTreeMap<&str, AccountSummary> = BTreeMap::new();
+    for job in jobs {
+        let summary = accounts
+            .entry(&job.account)
+            .or_insert_with(|| AccountSummary {
+                account: job.account.clone(),
+                ..Default::default()
+            });
+        if job.is_pending() {
+            summary.pending_jobs += 1;
+        } else if job.is_running() {
+            summary.running_jobs += 1;
+            if let Some(tres) = job.tres_parsed() {
+                let hours = job.elapsed_seconds().unwrap_or(0) as f64 / 3600.0;
+                summary.cpu_hours_running += tres.cpu as f64 * hours;
+                summary.gpu_hours_running += tres.gpu.map_or(0, |g| g.count) as f64 * hours;
+            }
+        }
+    }
+    accounts.into_values().collect()
+}
+
+/// The number of jobs in each state, sacct states like `CANCELLED by 1000`
+/// by their first word. Sorted by state.
+pub fn count_by_state(jobs: &[Job]) -> Vec<(String, usize)> {
+    let mut states: BTreeMap<&str, usize> = BTreeMap::new();
+    for job in jobs {
+        let state = job.state.split_whitespace().next().unwrap_or_default();
+        *states.entry(state).or_default() += 1;
+    }
+    states
+        .into_iter()
+        .map(|(state, count)| (state.to_owned(), count))
+        .collect()
+}
+
+/// Resources of the running jobs in a single partition.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PartitionSummary {
+    pub partition: String,
+    pub running_jobs: usize,
+    pub total_cpus: u32,
+    pub total_gpus: u32,
+}
+
+impl PartitionSummary {
+    /// Percentage of the CPUs of `partition` in use by these jobs.
+    pub fn cpu_utilization(&self, partition: &Partition) -> Option<u64> {
+        percent(self.total_cpus, partition.cpus_total)
+    }
+
+    /// Percentage of the GPUs of `partition` in use by these jobs.
+    pub fn gpu_utilization(&self, partition: &Partition) -> Option<u64> {
+        percent(self.total_gpus, partition.gpus_total)
+    }
+}
+
+fn percent(used: u32, total: u32) -> Option<u64> {
+    (total > 0).then(|| (used as u64 * 100 / total as u64).min(100))
+}
+
+/// Sorted by partition name.
+pub fn aggregate_by_partition(jobs: &[Job]) -> Vec<PartitionSummary> {
+    let mut partitions: BTreeMap<&str, PartitionSummary> = BTreeMap::new();
+    for job in jobs.iter().filter(|j| j.is_running()) {
+        let summary = partitions
+            .entry(&job.partition)
+            .or_insert_with(|| PartitionSummary {
+                partition: job.partition.clone(),
+                ..Default::default()
+            });
+        summary.running_jobs += 1;
+        if let Some(tres) = job.tres_parsed() {
+            summary.total_cpus = summary.total_cpus.saturating_add(tres.cpu);
+            summary.total_gpus = summary
+                .total_gpus
+                .saturating_add(tres.gpu.map_or(0, |g| g.count));
+        }
+    }
+    partitions.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(user: &str, state: &str, tres: &str) -> Job {
+        job_in("gpu", user, state, tres)
+    }
+
+    fn job_for(user: &str, state: &str, tres: &str, time: &str) -> Job {
+        Job {
+            time: time.to_owned(),
+            ..job(user, state, tres)
+        }
+    }
+
+    fn job_in(partition: &str, user: &str, state: &str, tres: &str) -> Job {
+        Job {
+            name: "train".to_owned(),
+            state: state.to_owned(),
+            state_compact: state[..1].to_owned(),
+            user: user.to_owned(),
+            tres: tres.to_owned(),
+            partition: partition.to_owned(),
+            ..Job::test_default()
+        }
+    }
+
+    #[test]
+    fn test_aggregate_by_user() {
+        let jobs = [
+            job("bob", "RUNNING", "cpu=4,mem=16G,node=1,gres/gpu=2"),
+            job_for("alice", "RUNNING", "cpu=2,mem=500M,node=1", "1:30:00"),
+            job_for("bob", "RUNNING", "cpu=1,mem=1G,node=1,gres/gpu=1", "10:00"),
+            job("bob", "PENDING", "cpu=8,mem=64G,node=1"),
+            job("alice", "COMPLETED", "cpu=8,mem=64G,node=1"),
+        ];
+        let summaries = aggregate_by_user(&jobs);
+        assert_eq!(
+            summaries,
+            [
+                UserSummary {
+                    username: "alice".to_owned(),
+                    running_jobs: 1,
+                    pending_jobs: 0,
+                    total_cpus: 2,
+                    total_gpus: 0,
+                    total_mem_mb: 500,
+                    cpu_seconds: 2 * 5400,
+                },
+                UserSummary {
+                    username: "bob".to_owned(),
+                    running_jobs: 2,
+                    pending_jobs: 1,
+                    total_cpus: 5,
+                    total_gpus: 3,
+                    total_mem_mb: 17 * 1024,
+                    cpu_seconds: 600,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_aggregate_by_account() {
+        let in_account = |account: &str, job: Job| Job {
+            account: account.to_owned(),
+            ..job
+        };
+        let jobs = [
+            in_account(
+                "proj2",
+                job_for("bob", "RUNNING", "cpu=4,node=1,gres/gpu=2", "1:30:00"),
+            ),
+            job_for("alice", "RUNNING", "cpu=2,node=1", "30:00"),
+            job_for("bob", "RUNNING", "cpu=8,node=1,gres/gpu=1", "2:00:00"),
+            job("bob", "PENDING", "cpu=8,node=1"),
+            job("alice", "COMPLETED", "cpu=8,node=1"),
+        ];
+        assert_eq!(
+            aggregate_by_account(&jobs),
+            [
+                AccountSummary {
+                    account: "proj1".to_owned(),
+                    running_jobs: 2,
+                    pending_jobs: 1,
+                    cpu_hours_running: 17.0,
+                    gpu_hours_running: 2.0,
+                },
+                AccountSummary {
+                    account: "proj2".to_owned(),
+                    running_jobs: 1,
+                    pending_jobs: 0,
+                    cpu_hours_running: 6.0,
+                    gpu_hours_running: 3.0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_count_by_state() {
+        let jobs = [
+            job("bob", "RUNNING", ""),
+            job("bob", "PENDING", ""),
+            job("alice", "RUNNING", ""),
+            job("alice", "CANCELLED by 1000", ""),
+        ];
+        assert_eq!(
+            count_by_state(&jobs),
+            [
+                ("CANCELLED".to_owned(), 1),
+                ("PENDING".to_owned(), 1),
+                ("RUNNING".to_owned(), 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_aggregate_by_partition() {
+        let jobs = [
+            job_in("gpu", "bob", "RUNNING", "cpu=4,mem=16G,node=1,gres/gpu=2"),
+            job_in("cpu", "alice", "RUNNING", "cpu=2,mem=500M,node=1"),
+            job_in("gpu", "alice", "RUNNING", "cpu=8,mem=1G,node=1,gres/gpu=1"),
+            job_in("gpu", "bob", "PENDING", "cpu=8,mem=64G,node=1"),
+        ];
+        let summaries = aggregate_by_partition(&jobs);
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].partition, "cpu");
+        let gpu = &summaries[1];
+        assert_eq!(
+            (gpu.running_jobs, gpu.total_cpus, gpu.total_gpus),
+            (2, 12, 3)
+        );
+
+        let partition = Partition {
+            name: "gpu".to_owned(),
+            state: "up".to_owned(),
+            nodes: 2,
+            cpus_total: 48,
+            cpus_idle: 36,
+            gpus_total: 0,
+        };
+        assert_eq!(gpu.cpu_utilization(&partition), Some(25));
+        assert_eq!(gpu.gpu_utilization(&partition), None);
+    }
+}
@@ -1,31 +1,143 @@
+use arboard::Clipboard;
 use crossbeam::{
-    channel::{unbounded, Receiver},
+    channel::{tick, unbounded, Receiver, Sender},
     select,
 };
 use itertools::Either;
-use std::{cmp::min, iter::once, path::PathBuf, process::Command};
-use std::{process::Stdio, time::Duration};
+use regex::Regex;
+use std::time::{Duration, Instant};
+use std::{
+    borrow::Cow,
+    cmp::{min, Reverse},
+    collections::{HashMap, HashSet},
+    env,
+    fs::File,
+    iter::once,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
+use crate::aggregate::{
+    aggregate_by_account, aggregate_by_partition, aggregate_by_user, count_by_state,
+    PartitionSummary,
+};
+use crate::cluster::cluster_name;
+use crate::columns::{Column, ColumnSet};
+use crate::config::Config;
+use crate::dependency::{dependency_graph, graph_lines};
+use crate::diff::{apply_changes, JobChange};
 use crate::file_watcher::{FileWatcherError, FileWatcherHandle};
-use crate::job_watcher::JobWatcherHandle;
+use crate::filter::JobFilter;
+use crate::job::{Job, JobParseError, JobSource};
+use crate::job_detail::JobDetail;
+use crate::job_watcher::{JobWatcherConfig, JobWatcherHandle};
+use crate::keybindings::{Action, Keybindings};
+use crate::mock::MockJobWatcherHandle;
+use crate::partition_watcher::{Partition, PartitionWatcherHandle};
+use crate::reason::reason_description;
+use crate::replay::Snapshot;
+use crate::sacct_watcher::SacctWatcherHandle;
+use crate::sort::{sort_jobs, SortKey};
+use crate::styles::state_style;
+use crate::theme::Theme;
+use crate::tres::format_mem_mb;
 
-use crossterm::event::{Event, KeyCode, KeyEvent};
+use chrono::{DateTime, Local, TimeDelta, Utc};
+use crossterm::{
+    event::{
+        DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, MouseButton, MouseEvent,
+        MouseEventKind,
+    },
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
 use ratatui::{
     backend::Backend,
-    layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    buffer::Buffer,
+    layout::{self, Constraint, Direction, Layout, Margin, Rect},
+    style::{Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    widgets::{
+        block::{Position, Title},
+        Bar, BarChart, BarGroup, Block, Borders, Clear, List, ListItem, ListState, Paragraph, Row,
+        Scrollbar, ScrollbarOrientation, ScrollbarState, Table, Widget, Wrap,
+    },
     Frame, Terminal,
 };
-use std::io;
+use std::io::{self, Write};
+
+/// How often the input thread checks whether it is paused.
+pub const INPUT_POLL_INTERVAL: Duration = Duration::from_millis(50);
 
+/// The line of the details panel that lists the dependencies.
+const DEPENDS_LINE: u16 = 2;
+
+#[derive(PartialEq, Eq)]
 pub enum Focus {
     Jobs,
+    Nodes,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    Jobs,
+    Users,
+    Partitions,
+    Dependencies,
+    Accounts,
+    /// Only totals, for `--overview`. There is no key to switch to or from it.
+    Overview,
 }
 
 pub enum Dialog {
     ConfirmCancelJob(String),
+    /// Waiting for scontrol to show the job.
+    LoadingJobDetail(String),
+    JobDetail {
+        detail: JobDetail,
+        scroll: u16,
+    },
+    ColumnPicker {
+        selected: usize,
+    },
+    Help {
+        scroll: u16,
+    },
+}
+
+/// A transient message shown in place of the help bar.
+pub struct Notification {
+    message: String,
+    is_error: bool,
+    created: Instant,
+}
+
+impl Notification {
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn info(message: String) -> Self {
+        Self {
+            message,
+            is_error: false,
+            created: Instant::now(),
+        }
+    }
+
+    fn error(message: String) -> Self {
+        Self {
+            message,
+            is_error: true,
+            created: Instant::now(),
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        self.created.elapsed() > Self::TIMEOUT
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -34,98 +146,306 @@ pub enum ScrollAnchor {
     Bottom,
 }
 
-#[derive(Default)]
+#[derive(Clone, Copy, Default)]
 pub enum OutputFileView {
     #[default]
     Stdout,
     Stderr,
 }
 
+/// A followed output file with its own scroll position.
+pub struct JobOutput {
+    content: Result<String, FileWatcherError>,
+    anchor: ScrollAnchor,
+    offset: u16,
+    watcher: FileWatcherHandle,
+}
+
+impl JobOutput {
+    fn new(watcher: FileWatcherHandle) -> Self {
+        Self {
+            content: Ok("".to_string()),
+            anchor: ScrollAnchor::Bottom,
+            offset: 0,
+            watcher,
+        }
+    }
+}
+
 pub struct App {
     focus: Focus,
+    tab: Tab,
+    /// The first node shown in the node list of the details panel.
+    node_scroll: usize,
     dialog: Option<Dialog>,
     jobs: Vec<Job>,
+    filter: JobFilter,
+    /// The name regex being typed after `/`, with the error if it does not compile.
+    filter_input: Option<(String, Option<String>)>,
+    /// The sort column and whether it is ascending, `None` keeps the squeue order.
+    sort: Option<(SortKey, bool)>,
+    /// `s` was pressed, the next key selects the sort column.
+    sort_pending: bool,
+    columns: ColumnSet,
+    /// Longer job names are truncated in the job list.
+    name_width: usize,
+    /// Running GPU jobs are marked in the job list after this long.
+    gpu_warning_after: Duration,
+    keybindings: Keybindings,
+    theme: Theme,
+    squeue_jobs: Vec<Job>,
+    sacct_jobs: Vec<Job>,
+    partitions: Vec<Partition>,
     job_list_state: ListState,
-    job_output: Result<String, FileWatcherError>,
-    job_output_anchor: ScrollAnchor,
-    job_output_offset: u16,
+    /// Where the job list was last drawn, for mouse clicks.
+    job_list_area: Rect,
+    job_stdout: JobOutput,
+    job_stderr: JobOutput,
+    /// Output paths of jobs seen in squeue, which sacct only guesses.
+    output_paths: HashMap<String, (Option<PathBuf>, Option<PathBuf>)>,
     job_output_wrap: bool,
-    _job_watcher: JobWatcherHandle,
-    job_output_watcher: FileWatcherHandle,
-    // sender: Sender<AppMessage>,
+    show_log: bool,
+    /// Only with [`JobFeed::Slurm`], like the partition watcher.
+    job_watcher: Option<JobWatcherHandle>,
+    _mock_watcher: Option<MockJobWatcherHandle>,
+    _partition_watcher: Option<PartitionWatcherHandle>,
+    _sacct_watcher: Option<SacctWatcherHandle>,
+    /// For threads that report back, like job commands.
+    sender: Sender<AppMessage>,
     receiver: Receiver<AppMessage>,
     input_receiver: Receiver<std::io::Result<Event>>,
+    /// Set while the TUI is suspended, so the input thread leaves the
+    /// terminal to the foreground command.
+    input_paused: Arc<AtomicBool>,
+    /// Run in the foreground by the run loop, with the TUI suspended.
+    foreground_command: Option<Command>,
     output_file_view: OutputFileView,
+    notification: Option<Notification>,
+    /// Rung after the next draw, the frame must not be interrupted by it.
+    bell: bool,
+    /// States that trigger a desktop notification when a job enters them.
+    notify_on: Vec<String>,
+    /// The last squeue error, cleared once squeue succeeds again.
+    squeue_error: Option<String>,
+    /// The last sacct failure, shown once.
+    sacct_error: Option<String>,
+    watcher_died: Option<String>,
+    /// A manual refresh was requested and squeue has not answered yet.
+    refreshing: bool,
+    /// Kept open, on X11 the copied text is gone once it is dropped.
+    clipboard: Option<Clipboard>,
+    /// Selected once it shows up in the job list.
+    select_on_load: Option<String>,
+    /// Where the job IDs of the `Depends` line were drawn, to select them on
+    /// click.
+    dependency_links: Vec<(Rect, String)>,
+    graph_scroll: u16,
+    refresh_interval: Duration,
+    /// When squeue last answered, for the status bar.
+    last_refresh: Option<DateTime<Local>>,
+    /// Redraws the status bar every second.
+    ticker: Receiver<Instant>,
+    /// Looked up once at startup.
+    cluster_name: Option<String>,
+    scontrol: PathBuf,
+    scancel: PathBuf,
+    /// When the replayed snapshot was taken, see [`App::replay`].
+    replay: Option<DateTime<Local>>,
 }
 
-pub struct Job {
-    pub job_id: String,
-    pub array_id: String,
-    pub array_step: Option<String>,
-    pub name: String,
-    pub state: String,
-    pub state_compact: String,
-    pub reason: Option<String>,
-    pub user: String,
-    pub time: String,
-    pub tres: String,
-    pub partition: String,
-    pub nodelist: String,
-    pub stdout: Option<PathBuf>,
-    pub stderr: Option<PathBuf>,
-    pub command: String,
-}
-
-impl Job {
-    fn id(&self) -> String {
-        match self.array_step.as_ref() {
-            Some(array_step) => format!("{}_{}", self.array_id, array_step),
-            None => self.job_id.clone(),
-        }
-    }
+/// Where the jobs of the TUI come from.
+pub enum JobFeed {
+    /// squeue, polled by the job watcher.
+    Slurm,
+    /// `--mock`, a JSON file read on every refresh.
+    Mock(PathBuf),
+    /// `turm replay`, a snapshot that is never refreshed.
+    Replay(Snapshot),
 }
 
 pub enum AppMessage {
     Jobs(Vec<Job>),
-    JobOutput(Result<String, FileWatcherError>),
+    /// What changed since the last `Jobs` or `JobsChanged`, see
+    /// [`crate::diff::apply_changes`].
+    JobsChanged(Vec<JobChange>),
+    SacctJobs(Vec<Job>),
+    Partitions(Vec<Partition>),
+    SqueueError(String),
+    /// sacct could not be run, the finished jobs are not updated.
+    SacctError(String),
+    /// Lines of squeue output that were skipped, sent before the jobs of
+    /// the same poll.
+    JobParseErrors(Vec<JobParseError>),
+    /// The job watcher gave up, the job list is no longer updated.
+    WatcherDied(String),
+    JobStateChanged {
+        id: String,
+        old_state: String,
+        new_state: String,
+    },
+    JobOutput(OutputFileView, Result<String, FileWatcherError>),
     Key(KeyEvent),
+    Mouse(MouseEvent),
+    CancelJob(String),
+    HoldJob(String),
+    ReleaseJob(String),
+    /// What scancel or scontrol reported, for the notification.
+    JobCommandDone(Result<String, String>),
+    JobDetail(String, Result<JobDetail, String>),
+    /// A desktop notification could not be shown.
+    Bell,
+    ClusterName(String),
+}
+
+/// What the TUI is started with, from the command line and the config file.
+pub struct AppConfig {
+    /// Seconds between polls of squeue, sinfo and sacct.
+    pub slurm_refresh_rate: u64,
+    /// Seconds between reads of the job output.
+    pub file_refresh_rate: u64,
+    pub job_watcher: JobWatcherConfig,
+    /// The job states to notify about.
+    pub notify_on: Vec<String>,
+    pub columns: ColumnSet,
+    pub sort: Option<(SortKey, bool)>,
+    pub name_width: usize,
+    pub gpu_warning_after: Duration,
+    pub keybindings: Keybindings,
+    pub theme: Theme,
+    pub feed: JobFeed,
 }
 
 impl App {
     pub fn new(
         input_receiver: Receiver<std::io::Result<Event>>,
-        slurm_refresh_rate: u64,
-        file_refresh_rate: u64,
-        squeue_args: Vec<String>,
+        input_paused: Arc<AtomicBool>,
+        config: AppConfig,
     ) -> App {
+        let AppConfig {
+            slurm_refresh_rate,
+            file_refresh_rate,
+            job_watcher,
+            notify_on,
+            columns,
+            sort,
+            name_width,
+            gpu_warning_after,
+            keybindings,
+            theme,
+            feed,
+        } = config;
         let (sender, receiver) = unbounded();
-        Self {
+        let slurm = matches!(feed, JobFeed::Slurm);
+        if slurm {
+            // scontrol can be slow, the TUI starts without waiting for it
+            let cluster_sender = sender.clone();
+            let scontrol = job_watcher.scontrol().to_owned();
+            std::thread::spawn(move || {
+                cluster_sender.send(AppMessage::ClusterName(cluster_name(&scontrol)))
+            });
+        }
+        let sacct = job_watcher.sacct_since();
+        let filter = job_watcher.job_filter().clone();
+        let mut app = Self {
             focus: Focus::Jobs,
+            tab: Tab::Jobs,
+            node_scroll: 0,
             dialog: None,
             jobs: Vec::new(),
-            _job_watcher: JobWatcherHandle::new(
-                sender.clone(),
-                Duration::from_secs(slurm_refresh_rate),
-                squeue_args,
-            ),
+            filter,
+            filter_input: None,
+            sort,
+            sort_pending: false,
+            columns,
+            name_width,
+            gpu_warning_after,
+            keybindings,
+            theme,
+            squeue_jobs: Vec::new(),
+            sacct_jobs: Vec::new(),
+            partitions: Vec::new(),
+            scontrol: job_watcher.scontrol().to_owned(),
+            scancel: job_watcher.scancel().to_owned(),
+            job_watcher: slurm.then(|| JobWatcherHandle::new(sender.clone(), job_watcher)),
+            _mock_watcher: match &feed {
+                JobFeed::Mock(file) => Some(MockJobWatcherHandle::new(
+                    sender.clone(),
+                    file.clone(),
+                    Duration::from_secs(slurm_refresh_rate),
+                )),
+                _ => None,
+            },
+            _partition_watcher: slurm.then(|| {
+                PartitionWatcherHandle::new(sender.clone(), Duration::from_secs(slurm_refresh_rate))
+            }),
+            _sacct_watcher: sacct.filter(|_| slurm).map(|since| {
+                SacctWatcherHandle::new(
+                    sender.clone(),
+                    Duration::from_secs(slurm_refresh_rate),
+                    since,
+                )
+            }),
+            job_list_area: Rect::default(),
             job_list_state: {
                 let mut s = ListState::default();
                 s.select(Some(0));
                 s
             },
-            job_output: Ok("".to_string()),
-            job_output_anchor: ScrollAnchor::Bottom,
-            job_output_offset: 0,
-            job_output_wrap: false,
-            job_output_watcher: FileWatcherHandle::new(
+            job_stdout: JobOutput::new(FileWatcherHandle::new(
                 sender.clone(),
+                OutputFileView::Stdout,
                 Duration::from_secs(file_refresh_rate),
-            ),
-            // sender,
-            receiver: receiver,
-            input_receiver: input_receiver,
+            )),
+            job_stderr: JobOutput::new(FileWatcherHandle::new(
+                sender.clone(),
+                OutputFileView::Stderr,
+                Duration::from_secs(file_refresh_rate),
+            )),
+            output_paths: HashMap::new(),
+            job_output_wrap: false,
+            show_log: true,
+            sender: sender.clone(),
+            receiver,
+            input_receiver,
+            input_paused,
+            foreground_command: None,
             output_file_view: OutputFileView::default(),
+            notification: None,
+            bell: false,
+            notify_on,
+            squeue_error: None,
+            sacct_error: None,
+            watcher_died: None,
+            refreshing: false,
+            clipboard: None,
+            select_on_load: None,
+            dependency_links: Vec::new(),
+            graph_scroll: 0,
+            refresh_interval: Duration::from_secs(slurm_refresh_rate),
+            last_refresh: None,
+            ticker: tick(Duration::from_secs(1)),
+            cluster_name: None,
+            replay: None,
+        };
+        match feed {
+            JobFeed::Slurm => {}
+            JobFeed::Mock(_) => app.cluster_name = Some("mock".to_owned()),
+            JobFeed::Replay(snapshot) => app.replay(snapshot),
         }
+        app
+    }
+
+    /// Shows the jobs of `snapshot` as if squeue had listed them.
+    fn replay(&mut self, snapshot: Snapshot) {
+        self.cluster_name = Some(
+            snapshot
+                .jobs
+                .iter()
+                .find_map(|j| j.cluster.clone())
+                .unwrap_or_else(|| "?".to_owned()),
+        );
+        self.replay = Some(snapshot.taken);
+        self.handle(AppMessage::Jobs(snapshot.jobs));
     }
 }
 
@@ -138,57 +458,282 @@ impl App {
                 recv(self.receiver) -> event => {
                     self.handle(event.unwrap());
                 }
+                recv(self.ticker) -> _ => {}
                 recv(self.input_receiver) -> input_res => {
                     match input_res.unwrap().unwrap() {
                         Event::Key(key) => {
-                            if key.code == KeyCode::Char('q') {
+                            // quit also closes the help
+                            if self.keybindings.quit.matches(key.code)
+                                && self.filter_input.is_none()
+                                && !matches!(self.dialog, Some(Dialog::Help { .. }))
+                            {
+                                if let Some(watcher) = &mut self.job_watcher {
+                                    watcher.shutdown();
+                                }
                                 return Ok(());
                             }
                             self.handle(AppMessage::Key(key));
                         },
+                        Event::Mouse(mouse) if matches!(
+                            mouse.kind,
+                            MouseEventKind::Down(MouseButton::Left)
+                                | MouseEventKind::ScrollUp
+                                | MouseEventKind::ScrollDown
+                        ) => {
+                            self.handle(AppMessage::Mouse(mouse));
+                        },
+                        // the layout is computed from the frame size on
+                        // every draw, the redraw below is all it takes
                         Event::Resize(_, _) => {},
                         _ => continue, // ignore and do not redraw
                     }
                 }
             };
 
+            if let Some(command) = self.foreground_command.take() {
+                self.run_foreground(terminal, command)?;
+            }
             terminal.draw(|f| self.ui(f))?;
+            if std::mem::take(&mut self.bell) {
+                let _ = io::stdout()
+                    .write_all(b"\x07")
+                    .and_then(|_| io::stdout().flush());
+            }
+        }
+    }
+
+    /// Leaves the TUI for an interactive command like ssh and restores it once
+    /// the command exits.
+    fn run_foreground<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        mut command: Command,
+    ) -> io::Result<()> {
+        self.input_paused.store(true, Ordering::SeqCst);
+        // give the input thread time to stop polling
+        std::thread::sleep(INPUT_POLL_INTERVAL);
+        disable_raw_mode()?;
+        execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
+        terminal.show_cursor()?;
+
+        let program = command.get_program().to_string_lossy().into_owned();
+        match command.status() {
+            Ok(status) if status.success() => {}
+            Ok(status) => {
+                self.notification = Some(Notification::error(format!(
+                    "{} exited with {}",
+                    program, status
+                )))
+            }
+            Err(e) => {
+                self.notification = Some(Notification::error(format!(
+                    "Failed to execute {}: {}",
+                    program, e
+                )))
+            }
         }
+
+        enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+        terminal.clear()?;
+        // drop keys read just before the input thread paused
+        while self.input_receiver.try_recv().is_ok() {}
+        self.input_paused.store(false, Ordering::SeqCst);
+        Ok(())
     }
 
     fn handle(&mut self, msg: AppMessage) {
         match msg {
-            AppMessage::Jobs(jobs) => self.jobs = jobs,
-            AppMessage::JobOutput(content) => self.job_output = content,
+            AppMessage::Jobs(jobs) => {
+                for j in &jobs {
+                    self.output_paths
+                        .insert(j.id(), (j.stdout.clone(), j.stderr.clone()));
+                }
+                self.squeue_jobs = jobs;
+                self.squeue_error = None;
+                self.last_refresh = Some(Local::now());
+                self.refreshing = false;
+                self.merge_jobs();
+            }
+            AppMessage::JobsChanged(changes) => {
+                for change in &changes {
+                    if let JobChange::Added(j)
+                    | JobChange::StateChanged { job: j, .. }
+                    | JobChange::Updated(j) = change
+                    {
+                        self.output_paths
+                            .insert(j.id(), (j.stdout.clone(), j.stderr.clone()));
+                    }
+                }
+                let changed = !changes.is_empty();
+                apply_changes(&mut self.squeue_jobs, changes);
+                self.squeue_error = None;
+                self.last_refresh = Some(Local::now());
+                self.refreshing = false;
+                if changed {
+                    self.merge_jobs();
+                }
+            }
+            AppMessage::SqueueError(e) => {
+                self.squeue_error = Some(e);
+                self.refreshing = false;
+            }
+            AppMessage::JobParseErrors(errors) => {
+                self.notification = Some(Notification::error(format!(
+                    "Skipped {} line(s) of squeue output: {}",
+                    errors.len(),
+                    errors[0]
+                )));
+            }
+            AppMessage::WatcherDied(e) => {
+                self.watcher_died = Some(e);
+                self.refreshing = false;
+            }
+            AppMessage::ClusterName(name) => self.cluster_name = Some(name),
+            AppMessage::SacctJobs(jobs) => {
+                self.sacct_jobs = jobs;
+                self.sacct_error = None;
+                self.merge_jobs();
+            }
+            AppMessage::SacctError(e) => {
+                // once, not on every poll
+                if self.sacct_error.as_ref() != Some(&e) {
+                    self.notification = Some(Notification::error(e.clone()));
+                }
+                self.sacct_error = Some(e);
+            }
+            AppMessage::Partitions(partitions) => self.partitions = partitions,
+            AppMessage::JobStateChanged {
+                id,
+                old_state,
+                new_state,
+            } => {
+                self.notify_state_change(&id, &old_state, &new_state);
+            }
+            AppMessage::CancelJob(id) => self.run_job_command(
+                &self.scancel.clone(),
+                &[&id],
+                format!("Cancelled job {}", id),
+            ),
+            AppMessage::HoldJob(id) => self.run_job_command(
+                &self.scontrol.clone(),
+                &["hold", &id],
+                format!("Held job {}", id),
+            ),
+            AppMessage::ReleaseJob(id) => self.run_job_command(
+                &self.scontrol.clone(),
+                &["release", &id],
+                format!("Released job {}", id),
+            ),
+            AppMessage::JobCommandDone(result) => {
+                self.notification = Some(match result {
+                    Ok(message) => Notification::info(message),
+                    Err(message) => Notification::error(message),
+                });
+                if let Some(watcher) = &self.job_watcher {
+                    watcher.poll_now();
+                }
+            }
+            AppMessage::Bell => self.bell = true,
+            AppMessage::JobDetail(id, result) => {
+                // dropped if the dialog was closed in the meantime
+                if matches!(&self.dialog, Some(Dialog::LoadingJobDetail(loading)) if *loading == id)
+                {
+                    match result {
+                        Ok(detail) => self.dialog = Some(Dialog::JobDetail { detail, scroll: 0 }),
+                        Err(e) => {
+                            self.dialog = None;
+                            self.notification = Some(Notification::error(e));
+                        }
+                    }
+                }
+            }
+            AppMessage::JobOutput(view, content) => match view {
+                OutputFileView::Stdout => self.job_stdout.content = content,
+                OutputFileView::Stderr => self.job_stderr.content = content,
+            },
+            AppMessage::Mouse(mouse) => self.handle_mouse(mouse),
             AppMessage::Key(key) => {
-                if let Some(dialog) = &self.dialog {
+                if self.filter_input.is_some() {
+                    self.handle_filter_input(key);
+                } else if self.sort_pending {
+                    self.sort_pending = false;
+                    if let Some(sort_key) = match key.code {
+                        KeyCode::Char(c) => SortKey::from_key(c),
+                        _ => None,
+                    } {
+                        self.sort = match self.sort {
+                            Some((current, ascending)) if current == sort_key => {
+                                Some((sort_key, !ascending))
+                            }
+                            _ => Some((sort_key, true)),
+                        };
+                        self.merge_jobs();
+                        self.save_view();
+                    }
+                } else if let Some(dialog) = &mut self.dialog {
+                    let action = self.keybindings.action(key.code);
                     match dialog {
                         Dialog::ConfirmCancelJob(id) => match key.code {
                             KeyCode::Enter | KeyCode::Char('y') => {
-                                Command::new("scancel")
-                                    .arg(id)
-                                    .stdout(Stdio::null())
-                                    .stderr(Stdio::null())
-                                    .spawn()
-                                    .expect("failed to execute scancel");
+                                let id = id.clone();
                                 self.dialog = None;
+                                self.handle(AppMessage::CancelJob(id));
                             }
                             KeyCode::Esc => {
                                 self.dialog = None;
                             }
                             _ => {}
                         },
-                    };
-                } else {
-                    match key.code {
-                        KeyCode::Char('h') | KeyCode::Left => self.focus_previous_panel(),
-                        KeyCode::Char('l') | KeyCode::Right => self.focus_next_panel(),
-                        KeyCode::Char('k') | KeyCode::Up => match self.focus {
-                            Focus::Jobs => self.select_previous_job(),
+                        Dialog::LoadingJobDetail(_) => match (key.code, action) {
+                            (KeyCode::Esc | KeyCode::Enter, _) | (_, Some(Action::Details)) => {
+                                self.dialog = None;
+                            }
+                            _ => {}
+                        },
+                        Dialog::JobDetail { scroll, .. } => match (key.code, action) {
+                            (_, Some(Action::Up)) => *scroll = scroll.saturating_sub(1),
+                            (_, Some(Action::Down)) => *scroll = scroll.saturating_add(1),
+                            (KeyCode::PageUp, _) => *scroll = scroll.saturating_sub(10),
+                            (KeyCode::PageDown, _) => *scroll = scroll.saturating_add(10),
+                            (KeyCode::Esc | KeyCode::Enter, _) | (_, Some(Action::Details)) => {
+                                self.dialog = None;
+                            }
+                            _ => {}
+                        },
+                        Dialog::Help { scroll } => match (key.code, action) {
+                            (_, Some(Action::Up)) => *scroll = scroll.saturating_sub(1),
+                            (_, Some(Action::Down)) => *scroll = scroll.saturating_add(1),
+                            (KeyCode::PageUp, _) => *scroll = scroll.saturating_sub(10),
+                            (KeyCode::PageDown, _) => *scroll = scroll.saturating_add(10),
+                            (KeyCode::Esc, _) | (_, Some(Action::Help | Action::Quit)) => {
+                                self.dialog = None;
+                            }
+                            _ => {}
                         },
-                        KeyCode::Char('j') | KeyCode::Down => match self.focus {
-                            Focus::Jobs => self.select_next_job(),
+                        Dialog::ColumnPicker { selected } => match (key.code, action) {
+                            (_, Some(Action::Up)) => *selected = selected.saturating_sub(1),
+                            (_, Some(Action::Down)) => {
+                                *selected = min(*selected + 1, self.columns.all().len() - 1)
+                            }
+                            (_, Some(Action::MoveColumnUp)) => {
+                                *selected = self.columns.move_up(*selected)
+                            }
+                            (_, Some(Action::MoveColumnDown)) => {
+                                *selected = self.columns.move_down(*selected)
+                            }
+                            (_, Some(Action::ToggleColumn)) => self.columns.toggle(*selected),
+                            (KeyCode::Esc | KeyCode::Enter, _) | (_, Some(Action::Columns)) => {
+                                self.dialog = None;
+                                self.save_view();
+                            }
+                            _ => {}
                         },
+                    };
+                } else if let Some(action) = self.keybindings.action(key.code) {
+                    self.handle_action(action);
+                } else if self.tab == Tab::Jobs {
+                    match key.code {
                         KeyCode::PageDown => {
                             let delta = if key.modifiers.intersects(
                                 crossterm::event::KeyModifiers::SHIFT
@@ -199,14 +744,13 @@ impl App {
                             } else {
                                 1
                             };
-                            match self.job_output_anchor {
+                            let output = self.job_output_mut();
+                            match output.anchor {
                                 ScrollAnchor::Top => {
-                                    self.job_output_offset =
-                                        self.job_output_offset.saturating_add(delta)
+                                    output.offset = output.offset.saturating_add(delta)
                                 }
                                 ScrollAnchor::Bottom => {
-                                    self.job_output_offset =
-                                        self.job_output_offset.saturating_sub(delta)
+                                    output.offset = output.offset.saturating_sub(delta)
                                 }
                             }
                         }
@@ -220,42 +764,25 @@ impl App {
                             } else {
                                 1
                             };
-                            match self.job_output_anchor {
+                            let output = self.job_output_mut();
+                            match output.anchor {
                                 ScrollAnchor::Top => {
-                                    self.job_output_offset =
-                                        self.job_output_offset.saturating_sub(delta)
+                                    output.offset = output.offset.saturating_sub(delta)
                                 }
                                 ScrollAnchor::Bottom => {
-                                    self.job_output_offset =
-                                        self.job_output_offset.saturating_add(delta)
+                                    output.offset = output.offset.saturating_add(delta)
                                 }
                             }
                         }
                         KeyCode::Home => {
-                            self.job_output_offset = 0;
-                            self.job_output_anchor = ScrollAnchor::Top;
+                            let output = self.job_output_mut();
+                            output.offset = 0;
+                            output.anchor = ScrollAnchor::Top;
                         }
                         KeyCode::End => {
-                            self.job_output_offset = 0;
-                            self.job_output_anchor = ScrollAnchor::Bottom;
-                        }
-                        KeyCode::Char('c') => {
-                            if let Some(id) = self
-                                .job_list_state
-                                .selected()
-                                .and_then(|i| self.jobs.get(i).map(|j| j.id()))
-                            {
-                                self.dialog = Some(Dialog::ConfirmCancelJob(id));
-                            }
-                        }
-                        KeyCode::Char('o') => {
-                            self.output_file_view = match self.output_file_view {
-                                OutputFileView::Stdout => OutputFileView::Stderr,
-                                OutputFileView::Stderr => OutputFileView::Stdout,
-                            };
-                        }
-                        KeyCode::Char('w') => {
-                            self.job_output_wrap = !self.job_output_wrap;
+                            let output = self.job_output_mut();
+                            output.offset = 0;
+                            output.anchor = ScrollAnchor::Bottom;
                         }
                         _ => {}
                     };
@@ -264,13 +791,13 @@ impl App {
         }
 
         // update
-        self.job_output_watcher
-            .set_file_path(self.job_list_state.selected().and_then(|i| {
-                self.jobs.get(i).and_then(|j| match self.output_file_view {
-                    OutputFileView::Stdout => j.stdout.clone(),
-                    OutputFileView::Stderr => j.stderr.clone(),
-                })
-            }));
+        let selected = self.selected_job().filter(|_| self.show_log);
+        let (stdout, stderr) = (
+            selected.and_then(|j| j.stdout.clone()),
+            selected.and_then(|j| j.stderr.clone()),
+        );
+        self.job_stdout.watcher.set_file_path(stdout);
+        self.job_stderr.watcher.set_file_path(stderr);
     }
 
     fn ui(&mut self, f: &mut Frame) {
@@ -278,72 +805,282 @@ impl App {
 
         let content_help = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Min(3), Constraint::Length(1)].as_ref())
+            .constraints(
+                [
+                    Constraint::Length(1),
+                    Constraint::Min(3),
+                    Constraint::Length(1),
+                    Constraint::Length(1),
+                ]
+                .as_ref(),
+            )
             .split(f.size());
+        f.render_widget(self.header(), content_help[0]);
+        f.render_widget(self.status_bar(), content_help[3]);
 
         let master_detail = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Min(50), Constraint::Percentage(70)].as_ref())
-            .split(content_help[0]);
+            .split(content_help[1]);
 
+        let detail_height = match self.selected_job() {
+            Some(j) if j.dependency.is_some() => 11,
+            _ => 10,
+        };
         let job_detail_log = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Length(7), Constraint::Min(3)].as_ref())
+            .constraints(if self.show_log {
+                [Constraint::Length(detail_height), Constraint::Min(3)]
+            } else {
+                [Constraint::Min(10), Constraint::Length(0)]
+            })
             .split(master_detail[1]);
 
         // Help
-        let help_options = vec![
-            ("q", "quit"),
-            ("⏶/⏷", "navigate"),
-            ("pgup/pgdown", "scroll"),
-            ("home/end", "top/bottom"),
-            ("esc", "cancel"),
-            ("enter", "confirm"),
-            ("c", "cancel job"),
-            ("o", "toggle stdout/stderr"),
-            ("w", "toggle text wrap"),
-        ];
-        let blue_style = Style::default().fg(Color::Blue);
-        let light_blue_style = Style::default().fg(Color::LightBlue);
+        let pending = self.selected_pending_job_id().is_some();
+        let running = self.selected_job().is_some_and(|j| j.is_running());
+        let kb = &self.keybindings;
+        let tabs = (
+            format!(
+                "{}/{}/{}/{}/{}",
+                kb.jobs_tab.label(),
+                kb.users_tab.label(),
+                kb.partitions_tab.label(),
+                kb.dependencies_tab.label(),
+                kb.accounts_tab.label()
+            ),
+            "jobs/users/partitions/dependencies/accounts",
+            true,
+        );
+        let help_options: Vec<(String, &str, bool)> = if self.sort_pending {
+            once(("esc".to_owned(), "cancel", true))
+                .chain(
+                    SortKey::ALL
+                        .iter()
+                        .map(|k| (k.key().to_string(), k.name(), true)),
+                )
+                .collect()
+        } else if self.tab == Tab::Overview {
+            vec![
+                (kb.quit.label(), "quit", true),
+                (kb.help.label(), "help", true),
+            ]
+        } else if self.tab != Tab::Jobs {
+            vec![
+                (kb.quit.label(), "quit", true),
+                (kb.help.label(), "help", true),
+                tabs,
+            ]
+        } else {
+            // enter also confirms dialogs, only listed once if it toggles the log
+            let toggle_log = Some(kb.toggle_log.label()).filter(|l| l != "enter");
+            let mut options = vec![
+                (kb.quit.label(), "quit", true),
+                (kb.help.label(), "help", true),
+                tabs,
+                ("⏶/⏷".to_owned(), "navigate", true),
+                ("pgup/pgdown".to_owned(), "scroll", true),
+                ("home/end".to_owned(), "top/bottom", true),
+                ("esc".to_owned(), "cancel", true),
+                (
+                    "enter".to_owned(),
+                    if toggle_log.is_some() {
+                        "confirm"
+                    } else {
+                        "confirm/toggle log"
+                    },
+                    true,
+                ),
+                (kb.cancel_job.label(), "cancel job", true),
+                (kb.details.label(), "details", true),
+                (kb.ssh.label(), "ssh", running),
+                (kb.attach.label(), "attach", running),
+                (kb.refresh.label(), "refresh", true),
+                (kb.copy_id.label(), "copy id", true),
+                (
+                    format!("{}/{}", kb.hold_job.label(), kb.release_job.label()),
+                    "hold/release job",
+                    pending,
+                ),
+                (kb.switch_output.label(), "toggle stdout/stderr", true),
+                (kb.wrap.label(), "toggle text wrap", true),
+                (kb.sort.label(), "sort", true),
+                (kb.columns.label(), "columns", true),
+                (kb.filter.label(), "filter by name", true),
+            ];
+            if let Some(key) = toggle_log {
+                options.insert(8, (key, "toggle log", true));
+            }
+            options
+        };
+        let blue_style = Style::default().fg(self.theme.key_fg);
+        let light_blue_style = Style::default().fg(self.theme.key_description_fg);
 
         let help = Line::from(help_options.iter().fold(
             Vec::new(),
-            |mut acc, (key, description)| {
+            |mut acc, (key, description, enabled)| {
                 if !acc.is_empty() {
                     acc.push(Span::raw(" | "));
                 }
-                acc.push(Span::styled(*key, blue_style));
+                let (key_style, description_style) = if *enabled {
+                    (blue_style, light_blue_style)
+                } else {
+                    let dim = Style::default().add_modifier(Modifier::DIM);
+                    (dim, dim)
+                };
+                acc.push(Span::styled(key.as_str(), key_style));
                 acc.push(Span::raw(": "));
-                acc.push(Span::styled(*description, light_blue_style));
+                acc.push(Span::styled(*description, description_style));
                 acc
             },
         ));
 
-        let help = Paragraph::new(help);
-        f.render_widget(help, content_help[1]);
+        let notification = self.notification.as_ref().filter(|n| !n.is_expired());
+        let help = match (&self.filter_input, notification) {
+            (Some((pattern, error)), _) => Paragraph::new(Line::from(vec![
+                Span::styled("/", blue_style),
+                Span::raw(pattern.as_str()),
+                Span::styled("█", Style::default().add_modifier(Modifier::SLOW_BLINK)),
+                Span::styled(
+                    error
+                        .as_ref()
+                        .map(|e| format!("  {}", e))
+                        .unwrap_or_default(),
+                    Style::default().fg(self.theme.error_fg),
+                ),
+            ])),
+            (None, Some(n)) => Paragraph::new(n.message.as_str()).style(if n.is_error {
+                Style::default().fg(self.theme.error_fg)
+            } else {
+                Style::default().fg(self.theme.success_fg)
+            }),
+            (None, None) => Paragraph::new(help),
+        };
+        f.render_widget(help, content_help[2]);
+
+        match self.tab {
+            Tab::Jobs => {}
+            Tab::Users => {
+                self.render_users(f, content_help[1]);
+                self.render_dialog(f);
+                self.render_watcher_died(f);
+                return;
+            }
+            Tab::Partitions => {
+                self.render_partitions(f, content_help[1]);
+                self.render_dialog(f);
+                self.render_watcher_died(f);
+                return;
+            }
+            Tab::Overview => {
+                self.render_overview(f, content_help[1]);
+                self.render_dialog(f);
+                self.render_watcher_died(f);
+                return;
+            }
+            Tab::Dependencies => {
+                self.render_dependencies(f, content_help[1]);
+                self.render_dialog(f);
+                self.render_watcher_died(f);
+                return;
+            }
+            Tab::Accounts => {
+                self.render_accounts(f, content_help[1]);
+                self.render_dialog(f);
+                self.render_watcher_died(f);
+                return;
+            }
+        }
 
         // Jobs
-        let max_id_len = self.jobs.iter().map(|j| j.id().len()).max().unwrap_or(0);
-        let max_user_len = self.jobs.iter().map(|j| j.user.len()).max().unwrap_or(0);
-        let max_partition_len = self
-            .jobs
+        let visible_jobs = self.filter.apply(&self.jobs);
+        let max_id_len = visible_jobs.iter().map(|j| j.id().len()).max().unwrap_or(0);
+        let max_user_len = visible_jobs.iter().map(|j| j.user.len()).max().unwrap_or(0);
+        let max_account_len = visible_jobs
+            .iter()
+            .map(|j| j.account.len())
+            .max()
+            .unwrap_or(0);
+        let max_cluster_len = visible_jobs
+            .iter()
+            .map(|j| j.cluster.as_deref().map_or(0, str::len))
+            .max()
+            .unwrap_or(0);
+        let max_partition_len = visible_jobs
             .iter()
             .map(|j| j.partition.len())
             .max()
             .unwrap_or(0);
-        let max_time_len = self.jobs.iter().map(|j| j.time.len()).max().unwrap_or(0);
-        let max_state_compact_len = self
-            .jobs
+        let max_time_len = visible_jobs.iter().map(|j| j.time.len()).max().unwrap_or(0);
+        let max_state_compact_len = visible_jobs
             .iter()
             .map(|j| j.state_compact.len())
             .max()
             .unwrap_or(0);
-        let jobs: Vec<ListItem> = self
-            .jobs
+        let max_time_limit_len = visible_jobs
+            .iter()
+            .map(|j| j.time_limit.as_deref().unwrap_or_default().len())
+            .max()
+            .unwrap_or(0);
+        let max_priority_len = visible_jobs
+            .iter()
+            .map(|j| j.priority.map_or(0, |p| p.to_string().len()))
+            .max()
+            .unwrap_or(0);
+        let show_progress = visible_jobs.iter().any(|j| time_used_fraction(j).is_some());
+        let gpu_warning = |j: &Job| j.gpu_running_longer_than(self.gpu_warning_after.as_secs());
+        let show_gpu_warning = visible_jobs.iter().any(|j| gpu_warning(j));
+        let columns: Vec<Column> = self
+            .columns
+            .visible()
+            .filter(|&c| c != Column::Progress || show_progress)
+            .filter(|&c| c != Column::GpuWarning || show_gpu_warning)
+            .collect();
+        // names get what the other columns leave of the list, so they shrink
+        // with the terminal instead of being cut off by the border
+        let other_columns_width: usize = columns
             .iter()
+            .map(|column| match column {
+                Column::State => max_state_compact_len + 1,
+                Column::JobId => max_id_len + 1,
+                Column::Cluster => max_cluster_len + 1,
+                Column::Partition => max_partition_len + 1,
+                Column::User => max_user_len + 1,
+                Column::Account => max_account_len + 1,
+                Column::Time => max_time_len + 1,
+                Column::TimeLimit => max_time_limit_len + 1,
+                Column::Priority => max_priority_len + 1,
+                Column::Progress => PROGRESS_BAR_WIDTH + 1,
+                Column::GpuWarning => 2,
+                Column::Name => 0,
+            })
+            .sum();
+        let name_width = self.name_width.min(
+            (master_detail[0].width.saturating_sub(2) as usize)
+                .saturating_sub(other_columns_width)
+                .max(NAME_WIDTH_STEP),
+        );
+        let max_name_len = visible_jobs
+            .iter()
+            .map(|j| j.name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(name_width);
+        // only the rows that fit are built, the list can have thousands
+        let rows = master_detail[0].height.saturating_sub(2) as usize;
+        let offset = scroll_offset(
+            self.job_list_state.offset(),
+            self.job_list_state.selected(),
+            rows,
+            visible_jobs.len(),
+        );
+        let jobs: Vec<ListItem> = visible_jobs
+            .iter()
+            .skip(offset)
+            .take(rows)
             .map(|j| {
-                ListItem::new(Line::from(vec![
-                    Span::styled(
+                let spans = columns.iter().enumerate().map(|(i, column)| match column {
+                    Column::State => Span::styled(
                         format!(
                             "{:<max$.max$}",
                             j.state_compact,
@@ -351,60 +1088,171 @@ impl App {
                         ),
                         Style::default(),
                     ),
-                    Span::raw(" "),
-                    Span::styled(
+                    Column::JobId => Span::styled(
                         format!("{:<max$.max$}", j.id(), max = max_id_len),
-                        Style::default().fg(Color::Yellow),
+                        Style::default(),
                     ),
-                    Span::raw(" "),
-                    Span::styled(
+                    Column::Cluster => Span::styled(
+                        format!(
+                            "{:<max$.max$}",
+                            j.cluster.as_deref().unwrap_or_default(),
+                            max = max_cluster_len
+                        ),
+                        Style::default(),
+                    ),
+                    Column::Partition => Span::styled(
                         format!("{:<max$.max$}", j.partition, max = max_partition_len),
-                        Style::default().fg(Color::Blue),
+                        Style::default(),
                     ),
-                    Span::raw(" "),
-                    Span::styled(
+                    Column::User => Span::styled(
                         format!("{:<max$.max$}", j.user, max = max_user_len),
-                        Style::default().fg(Color::Green),
+                        Style::default(),
                     ),
-                    Span::raw(" "),
-                    Span::styled(
+                    Column::Account => Span::styled(
+                        format!("{:<max$.max$}", j.account, max = max_account_len),
+                        Style::default(),
+                    ),
+                    Column::Time => Span::styled(
                         format!("{:>max$.max$}", j.time, max = max_time_len),
-                        Style::default().fg(Color::Red),
+                        Style::default(),
                     ),
-                    Span::raw(" "),
-                    Span::raw(&j.name),
-                ]))
+                    Column::TimeLimit => Span::styled(
+                        format!(
+                            "{:>max$.max$}",
+                            j.time_limit.as_deref().unwrap_or_default(),
+                            max = max_time_limit_len
+                        ),
+                        Style::default(),
+                    ),
+                    Column::Priority => Span::styled(
+                        format!(
+                            "{:>max$}",
+                            j.priority.map(|p| p.to_string()).unwrap_or_default(),
+                            max = max_priority_len
+                        ),
+                        Style::default(),
+                    ),
+                    Column::Progress => match time_used_fraction(j) {
+                        Some(fraction) => progress_bar(fraction, &self.theme),
+                        None => Span::raw(" ".repeat(PROGRESS_BAR_WIDTH)),
+                    },
+                    Column::GpuWarning if gpu_warning(j) => Span::styled(
+                        "!",
+                        Style::default()
+                            .fg(self.theme.error_fg)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Column::GpuWarning => Span::raw(" "),
+                    // only padded if other columns follow
+                    Column::Name if i + 1 < columns.len() => Span::raw(format!(
+                        "{:<max$}",
+                        truncate(&j.name, name_width),
+                        max = max_name_len
+                    )),
+                    Column::Name => Span::raw(truncate(&j.name, name_width)),
+                });
+                let spans: Vec<Span> = spans.flat_map(|s| [Span::raw(" "), s]).skip(1).collect();
+                // the whole row in the color of its state
+                let style = state_style(&self.theme, &j.state);
+                ListItem::new(Line::from(spans)).style(match j.source {
+                    JobSource::Squeue => style,
+                    JobSource::Sacct => style.add_modifier(Modifier::DIM),
+                })
             })
             .collect();
         let job_list = List::new(jobs)
             .block(
                 Block::default()
-                    .title(format!("Jobs ({})", self.jobs.len()))
+                    .title(Line::from(vec![
+                        Span::raw(if self.filter.is_empty() {
+                            format!("Jobs ({})", self.jobs.len())
+                        } else {
+                            format!("Jobs ({}/{})", visible_jobs.len(), self.jobs.len())
+                        }),
+                        Span::raw(match self.sort {
+                            Some((key, true)) => format!(" {} ▲", key.name()),
+                            Some((key, false)) => format!(" {} ▼", key.name()),
+                            None => "".to_string(),
+                        }),
+                        Span::styled(
+                            match (&self.filter_input, &self.filter.name_regex) {
+                                (None, Some(r)) => format!(" /{}/", r),
+                                _ => "".to_string(),
+                            },
+                            Style::default().fg(self.theme.key_fg),
+                        ),
+                        Span::styled(
+                            if self.refreshing {
+                                " refreshing..."
+                            } else {
+                                ""
+                            },
+                            Style::default().add_modifier(Modifier::DIM),
+                        ),
+                    ]))
+                    .title(
+                        Title::from(Span::styled(
+                            self.squeue_error
+                                .as_deref()
+                                .and_then(|e| e.lines().next())
+                                .unwrap_or_default(),
+                            Style::default().fg(self.theme.error_fg),
+                        ))
+                        .position(Position::Bottom),
+                    )
                     .borders(Borders::ALL)
                     .border_style(if self.dialog.is_some() {
                         Style::default()
                     } else {
                         match self.focus {
-                            Focus::Jobs => Style::default().fg(Color::Green),
+                            Focus::Jobs => Style::default().fg(self.theme.border_fg),
+                            Focus::Nodes => Style::default(),
                         }
                     }),
             )
-            .highlight_style(Style::default().bg(Color::Green).fg(Color::Black));
-        f.render_stateful_widget(job_list, master_detail[0], &mut self.job_list_state);
+            .highlight_style(self.theme.selected());
+        let mut window_state = ListState::default().with_selected(
+            self.job_list_state
+                .selected()
+                .map(|i| i.saturating_sub(offset)),
+        );
+        f.render_stateful_widget(job_list, master_detail[0], &mut window_state);
+        *self.job_list_state.offset_mut() = offset;
+        self.job_list_area = master_detail[0];
+
+        // on the right border, only if the jobs do not fit
+        if visible_jobs.len() > rows {
+            let mut scrollbar_state = ScrollbarState::new(visible_jobs.len() - rows + 1)
+                .position(self.job_list_state.offset())
+                .viewport_content_length(rows);
+            f.render_stateful_widget(
+                Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                    .begin_symbol(None)
+                    .end_symbol(None),
+                master_detail[0].inner(Margin {
+                    vertical: 1,
+                    horizontal: 0,
+                }),
+                &mut scrollbar_state,
+            );
+        }
 
         // Job details
 
         let job_detail = self
             .job_list_state
             .selected()
-            .and_then(|i| self.jobs.get(i));
+            .and_then(|i| visible_jobs.get(i));
+        let nodes = job_detail.map(|j| j.nodes()).unwrap_or_default();
 
+        // (x, width, job ID) in the `Depends` line
+        let mut dependency_links = Vec::new();
         let job_detail = job_detail.map(|j| {
             let state = Line::from(vec![
-                Span::styled("State  ", Style::default().fg(Color::Yellow)),
+                Span::styled("State  ", Style::default().fg(self.theme.label_fg)),
                 Span::raw(" "),
                 Span::raw(&j.state),
-                if let Some(s) = j.reason.as_deref() {
+                if let Some(s) = &j.reason {
                     Span::styled(
                         format!(" ({s})"),
                         Style::default().add_modifier(Modifier::DIM),
@@ -412,29 +1260,134 @@ impl App {
                 } else {
                     Span::raw("")
                 },
+                Span::styled(
+                    j.wait_so_far()
+                        .map(|d| format!(" for {}", human_duration(d)))
+                        .unwrap_or_default(),
+                    Style::default()
+                        .fg(self.theme.pending_fg)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                // only pending jobs wait for their reason
+                Span::styled(
+                    j.reason
+                        .as_ref()
+                        .filter(|_| j.is_pending())
+                        .map(reason_description)
+                        .filter(|d| !d.is_empty())
+                        .map(|d| format!(" - {}", d))
+                        .unwrap_or_default(),
+                    Style::default().add_modifier(Modifier::ITALIC),
+                ),
+            ]);
+
+            let name = Line::from(vec![
+                Span::styled("Name   ", Style::default().fg(self.theme.label_fg)),
+                Span::raw(" "),
+                Span::raw(&j.name),
+            ]);
+
+            // job IDs that are still listed can be clicked
+            let mut depends = vec![
+                Span::styled("Depends", Style::default().fg(self.theme.label_fg)),
+                Span::raw(" "),
+            ];
+            for (i, dependency) in j.dependencies().into_iter().enumerate() {
+                if i > 0 {
+                    depends.push(Span::raw(", "));
+                }
+                depends.push(Span::raw(dependency.kind));
+                for id in dependency.job_ids {
+                    let listed = visible_jobs
+                        .iter()
+                        .any(|j| j.id() == id || j.array_id == id);
+                    depends.push(Span::raw(" "));
+                    depends.push(if listed {
+                        let x = depends.iter().map(Span::width).sum::<usize>() as u16;
+                        dependency_links.push((x, id.len() as u16, id.clone()));
+                        Span::styled(id, Style::default().add_modifier(Modifier::UNDERLINED))
+                    } else {
+                        Span::styled(id, Style::default().add_modifier(Modifier::DIM))
+                    });
+                }
+            }
+
+            let submit = Line::from(vec![
+                Span::styled("Submit ", Style::default().fg(self.theme.label_fg)),
+                Span::raw(" "),
+                Span::raw(
+                    j.submit_time
+                        .map(|t| relative_time(Utc::now() - t))
+                        .unwrap_or_default(),
+                ),
+                Span::styled(
+                    j.submit_time
+                        .map(|t| format!(" ({})", t.with_timezone(&Local).format("%F %T")))
+                        .unwrap_or_default(),
+                    Style::default().add_modifier(Modifier::DIM),
+                ),
+                Span::raw(
+                    j.wait_duration()
+                        .map(|d| format!(", waited {}", human_duration(d)))
+                        .unwrap_or_default(),
+                ),
+                Span::styled(
+                    if j.submit_node.is_some() {
+                        "  Batch host "
+                    } else {
+                        ""
+                    },
+                    Style::default().fg(self.theme.label_fg),
+                ),
+                Span::raw(j.submit_node.as_deref().unwrap_or_default()),
             ]);
 
             let command = Line::from(vec![
-                Span::styled("Command", Style::default().fg(Color::Yellow)),
+                Span::styled("Command", Style::default().fg(self.theme.label_fg)),
                 Span::raw(" "),
                 Span::raw(&j.command),
             ]);
-            let nodes = Line::from(vec![
-                Span::styled("Nodes  ", Style::default().fg(Color::Yellow)),
+            let workdir = Line::from(vec![
+                Span::styled("WorkDir", Style::default().fg(self.theme.label_fg)),
+                Span::raw(" "),
+                Span::raw(j.workdir().map(|p| p.to_string_lossy()).unwrap_or_default()),
+            ]);
+            let qos = Line::from(vec![
+                Span::styled("QOS    ", Style::default().fg(self.theme.label_fg)),
                 Span::raw(" "),
-                Span::raw(&j.nodelist),
+                Span::raw(&j.qos),
+                Span::styled("  Account ", Style::default().fg(self.theme.label_fg)),
+                Span::raw(&j.account),
+                Span::styled(
+                    if j.reservation.is_some() {
+                        "  Reservation "
+                    } else {
+                        ""
+                    },
+                    Style::default().fg(self.theme.label_fg),
+                ),
+                Span::raw(j.reservation.as_deref().unwrap_or_default()),
             ]);
+            let gpus = j
+                .tres_parsed()
+                .and_then(|t| t.gpu)
+                .and_then(|g| Some(format!("{} × {}", g.count, g.model?)));
             let tres = Line::from(vec![
-                Span::styled("TRES   ", Style::default().fg(Color::Yellow)),
+                Span::styled("TRES   ", Style::default().fg(self.theme.label_fg)),
                 Span::raw(" "),
                 Span::raw(&j.tres),
+                Span::styled(
+                    if gpus.is_some() { "  GPUs " } else { "" },
+                    Style::default().fg(self.theme.label_fg),
+                ),
+                Span::raw(gpus.unwrap_or_default()),
             ]);
             let ui_stdout_text = match self.output_file_view {
                 OutputFileView::Stdout => "stdout ",
                 OutputFileView::Stderr => "stderr ",
             };
             let stdout = Line::from(vec![
-                Span::styled(ui_stdout_text, Style::default().fg(Color::Yellow)),
+                Span::styled(ui_stdout_text, Style::default().fg(self.theme.label_fg)),
                 Span::raw(" "),
                 Span::raw(
                     match self.output_file_view {
@@ -447,25 +1400,80 @@ impl App {
                 ),
             ]);
 
-            Text::from(vec![state, command, nodes, tres, stdout])
+            let mut lines = vec![name, state];
+            if j.dependency.is_some() {
+                lines.push(Line::from(depends));
+            }
+            lines.extend([submit, command, workdir, qos, tres, stdout]);
+            Text::from(lines)
         });
         let job_detail = Paragraph::new(job_detail.unwrap_or_default())
             .block(Block::default().title("Details").borders(Borders::ALL));
-        f.render_widget(job_detail, job_detail_log[0]);
+        let nodes_width = match nodes.iter().map(|n| n.len()).max() {
+            Some(width) => width.max(10) + 2,
+            None => 0,
+        };
+        let detail_nodes = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(nodes_width as u16)])
+            .split(job_detail_log[0]);
+        f.render_widget(job_detail, detail_nodes[0]);
+        let inner = detail_nodes[0].inner(Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+        self.dependency_links = dependency_links
+            .into_iter()
+            .map(|(x, width, id)| {
+                let area = Rect {
+                    x: inner.x + x,
+                    y: inner.y + DEPENDS_LINE,
+                    width,
+                    height: 1,
+                };
+                (area.intersection(inner), id)
+            })
+            .collect();
+        if !nodes.is_empty() {
+            let focused = self.focus == Focus::Nodes && self.dialog.is_none();
+            render_nodes(
+                f,
+                detail_nodes[1],
+                &nodes,
+                &mut self.node_scroll,
+                focused,
+                &self.theme,
+            );
+        }
 
         // Log
         let log_area = job_detail_log[1];
+        let tab_style = |active: bool| {
+            if active {
+                Style::default()
+                    .fg(self.theme.border_fg)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().add_modifier(Modifier::DIM)
+            }
+        };
+        let job_output = self.job_output();
         let log_title = Line::from(vec![
-            Span::raw(match self.output_file_view {
-                OutputFileView::Stdout => "stdout",
-                OutputFileView::Stderr => "stderr",
-            }),
             Span::styled(
-                match self.job_output_anchor {
-                    ScrollAnchor::Top if self.job_output_offset == 0 => "[T]".to_string(),
-                    ScrollAnchor::Top => format!("[T+{}]", self.job_output_offset),
-                    ScrollAnchor::Bottom if self.job_output_offset == 0 => "".to_string(),
-                    ScrollAnchor::Bottom => format!("[B-{}]", self.job_output_offset),
+                "stdout",
+                tab_style(matches!(self.output_file_view, OutputFileView::Stdout)),
+            ),
+            Span::raw("│"),
+            Span::styled(
+                "stderr",
+                tab_style(matches!(self.output_file_view, OutputFileView::Stderr)),
+            ),
+            Span::styled(
+                match job_output.anchor {
+                    ScrollAnchor::Top if job_output.offset == 0 => "[T]".to_string(),
+                    ScrollAnchor::Top => format!("[T+{}]", job_output.offset),
+                    ScrollAnchor::Bottom if job_output.offset == 0 => "".to_string(),
+                    ScrollAnchor::Bottom => format!("[B-{}]", job_output.offset),
                 },
                 Style::default().add_modifier(Modifier::DIM),
             ),
@@ -484,64 +1492,749 @@ impl App {
         //     "".to_string()
         // });
 
-        let log = match self.job_output.as_deref() {
+        let has_path = self
+            .job_list_state
+            .selected()
+            .and_then(|i| visible_jobs.get(i))
+            .map(|j| match self.output_file_view {
+                OutputFileView::Stdout => j.stdout.is_some(),
+                OutputFileView::Stderr => j.stderr.is_some(),
+            });
+        let log = match job_output.content.as_deref() {
+            _ if has_path == Some(false) => Paragraph::new(match self.output_file_view {
+                OutputFileView::Stdout => "No stdout file for this job",
+                OutputFileView::Stderr => "No stderr file for this job",
+            })
+            .style(Style::default().add_modifier(Modifier::DIM)),
             Ok(s) => Paragraph::new(fit_text(
                 s,
                 log_block.inner(log_area).height as usize,
                 log_block.inner(log_area).width as usize,
-                self.job_output_anchor,
-                self.job_output_offset as usize,
+                job_output.anchor,
+                job_output.offset as usize,
                 self.job_output_wrap,
             )),
             Err(e) => Paragraph::new(e.to_string())
-                .style(Style::default().fg(Color::Red))
+                .style(Style::default().fg(self.theme.error_fg))
                 .wrap(Wrap { trim: true }),
         }
         .block(log_block);
 
-        f.render_widget(log, log_area);
-
-        if let Some(dialog) = &self.dialog {
-            fn centered_lines(percent_x: u16, lines: u16, r: Rect) -> Rect {
-                let dy = r.height.saturating_sub(lines) / 2;
-                let r = Rect::new(r.x, r.y + dy, r.width, min(lines, r.height - dy));
-
-                Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints(
-                        [
-                            Constraint::Percentage((100 - percent_x) / 2),
-                            Constraint::Percentage(percent_x),
-                            Constraint::Percentage((100 - percent_x) / 2),
-                        ]
-                        .as_ref(),
-                    )
-                    .split(r)[1]
-            }
+        if self.show_log {
+            f.render_widget(log, log_area);
+        }
 
-            match dialog {
-                Dialog::ConfirmCancelJob(id) => {
-                    let dialog = Paragraph::new(Line::from(vec![
+        self.render_dialog(f);
+
+        self.render_watcher_died(f);
+    }
+
+    fn header(&self) -> Paragraph<'_> {
+        Paragraph::new(Line::from(vec![
+            Span::styled(" turm", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" on "),
+            Span::styled(
+                self.cluster_name.as_deref().unwrap_or("…"),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+        ]))
+        .style(
+            Style::default()
+                .fg(self.theme.status_fg)
+                .bg(self.theme.status_bg),
+        )
+    }
+
+    /// Filter, sort order, job counts and when the job list is refreshed.
+    fn status_bar(&self) -> Paragraph<'_> {
+        let visible_jobs = self.filter.apply(&self.jobs);
+        let running = visible_jobs.iter().filter(|j| j.is_running()).count();
+        let pending = visible_jobs.iter().filter(|j| j.is_pending()).count();
+
+        let mut parts = Vec::new();
+        if !self.filter.is_empty() {
+            parts.push(self.filter.to_string());
+        }
+        if let Some((key, ascending)) = self.sort {
+            parts.push(format!(
+                "sorted by {} {}",
+                key.name(),
+                if ascending { "▲" } else { "▼" }
+            ));
+        }
+        parts.push(format!(
+            "{} jobs, {} running, {} pending",
+            visible_jobs.len(),
+            running,
+            pending
+        ));
+        parts.push(match (self.replay, self.last_refresh) {
+            (Some(taken), _) => format!("REPLAY MODE, snapshot of {}", taken.format("%F %T")),
+            (None, Some(last)) => {
+                let next = last + self.refresh_interval - Local::now();
+                format!(
+                    "refreshed {}, next in {}s",
+                    last.format("%T"),
+                    next.num_seconds().max(0)
+                )
+            }
+            (None, None) => "waiting for squeue".to_owned(),
+        });
+
+        Paragraph::new(parts.join(" | ")).style(
+            Style::default()
+                .fg(self.theme.status_fg)
+                .bg(self.theme.status_bg),
+        )
+    }
+
+    fn render_dialog(&mut self, f: &mut Frame) {
+        if let Some(dialog) = &mut self.dialog {
+            match dialog {
+                Dialog::ConfirmCancelJob(id) => {
+                    let dialog = Paragraph::new(Line::from(vec![
                         Span::raw("Cancel job "),
-                        Span::styled(id, Style::default().add_modifier(Modifier::BOLD)),
+                        Span::styled(id.as_str(), Style::default().add_modifier(Modifier::BOLD)),
                         Span::raw("?"),
                     ]))
-                    .style(Style::default().fg(Color::White))
+                    .style(Style::default().fg(self.theme.text_fg))
                     .wrap(Wrap { trim: true })
                     .block(
                         Block::default()
                             .title("Confirm")
                             .borders(Borders::ALL)
-                            .style(Style::default().fg(Color::Green)),
+                            .style(Style::default().fg(self.theme.border_fg)),
                     );
 
                     let area = centered_lines(75, 3, f.size());
                     f.render_widget(Clear, area);
                     f.render_widget(dialog, area);
                 }
+                Dialog::LoadingJobDetail(id) => {
+                    let dialog = Paragraph::new("Loading...")
+                        .style(Style::default().fg(self.theme.text_fg))
+                        .block(
+                            Block::default()
+                                .title(format!("Job {}", id))
+                                .borders(Borders::ALL)
+                                .style(Style::default().fg(self.theme.border_fg)),
+                        );
+
+                    let area = centered_lines(75, 3, f.size());
+                    f.render_widget(Clear, area);
+                    f.render_widget(dialog, area);
+                }
+                Dialog::JobDetail { detail, scroll } => {
+                    let fields = detail.sorted_fields();
+                    let max_key_len = fields.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+                    let lines: Vec<Line> = fields
+                        .iter()
+                        .map(|(k, v)| {
+                            Line::from(vec![
+                                Span::styled(
+                                    format!("{:<max$}", k, max = max_key_len),
+                                    Style::default().fg(self.theme.label_fg),
+                                ),
+                                Span::raw(" "),
+                                Span::raw(*v),
+                            ])
+                        })
+                        .collect();
+
+                    let height = min(lines.len() as u16 + 2, f.size().height.saturating_sub(4));
+                    let area = centered_lines(75, height, f.size());
+                    // keep the last field in view
+                    let max_scroll =
+                        (lines.len() as u16).saturating_sub(area.height.saturating_sub(2));
+                    *scroll = min(*scroll, max_scroll);
+
+                    let dialog = Paragraph::new(lines)
+                        .scroll((*scroll, 0))
+                        .style(Style::default().fg(self.theme.text_fg))
+                        .block(
+                            Block::default()
+                                .title(format!("Job {}", detail.job_id))
+                                .borders(Borders::ALL)
+                                .style(Style::default().fg(self.theme.border_fg)),
+                        );
+                    f.render_widget(Clear, area);
+                    f.render_widget(dialog, area);
+                }
+                Dialog::Help { scroll } => {
+                    let keys: Vec<(String, &str)> = self
+                        .keybindings
+                        .bindings()
+                        .into_iter()
+                        .map(|(action, keys)| (keys.label(), action.description()))
+                        .chain(FIXED_KEYS.iter().map(|(k, d)| (k.to_string(), *d)))
+                        .collect();
+                    let max_key_len = keys.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+                    let lines: Vec<Line> = keys
+                        .iter()
+                        .map(|(k, d)| {
+                            Line::from(vec![
+                                Span::styled(
+                                    format!("{:<max$}", k, max = max_key_len),
+                                    Style::default().fg(self.theme.key_fg),
+                                ),
+                                Span::raw("  "),
+                                Span::raw(*d),
+                            ])
+                        })
+                        .collect();
+
+                    let height = min(lines.len() as u16 + 2, f.size().height.saturating_sub(4));
+                    let area = centered_lines(60, height, f.size());
+                    let max_scroll =
+                        (lines.len() as u16).saturating_sub(area.height.saturating_sub(2));
+                    *scroll = min(*scroll, max_scroll);
+
+                    let dialog = Paragraph::new(lines)
+                        .scroll((*scroll, 0))
+                        .style(Style::default().fg(self.theme.text_fg))
+                        .block(
+                            Block::default()
+                                .title("Keys")
+                                .borders(Borders::ALL)
+                                .style(Style::default().fg(self.theme.border_fg)),
+                        );
+                    f.render_widget(Clear, area);
+                    f.render_widget(dialog, area);
+                }
+                Dialog::ColumnPicker { selected } => {
+                    let items: Vec<ListItem> = self
+                        .columns
+                        .all()
+                        .iter()
+                        .map(|(column, visible)| {
+                            let item = ListItem::new(format!(
+                                "[{}] {}",
+                                if *visible { "x" } else { " " },
+                                column.name()
+                            ));
+                            if column.is_required() {
+                                item.style(Style::default().add_modifier(Modifier::DIM))
+                            } else {
+                                item
+                            }
+                        })
+                        .collect();
+                    let height = items.len() as u16 + 2;
+                    let list = List::new(items)
+                        .style(Style::default().fg(self.theme.text_fg))
+                        .highlight_style(self.theme.selected())
+                        .block(
+                            Block::default()
+                                .title("Columns (space: toggle, J/K: move)")
+                                .borders(Borders::ALL)
+                                .style(Style::default().fg(self.theme.border_fg)),
+                        );
+
+                    let area = centered_lines(40, height, f.size());
+                    f.render_widget(Clear, area);
+                    f.render_stateful_widget(
+                        list,
+                        area,
+                        &mut ListState::default().with_selected(Some(*selected)),
+                    );
+                }
             }
         }
     }
+
+    fn render_watcher_died(&self, f: &mut Frame) {
+        if let Some(e) = &self.watcher_died {
+            let text = vec![
+                Line::from(Span::styled(
+                    e.as_str(),
+                    Style::default().add_modifier(Modifier::BOLD),
+                )),
+                Line::from(""),
+                Line::from(format!(
+                    "The job list is no longer updated. Press {} to quit.",
+                    self.keybindings.quit.label()
+                )),
+            ];
+            let height = min(5, f.size().height);
+            let area = centered_lines(75, height, f.size());
+            let dialog = Paragraph::new(text)
+                .style(Style::default().fg(self.theme.error_fg))
+                .wrap(Wrap { trim: true })
+                .block(
+                    Block::default()
+                        .title("Job watcher stopped")
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(self.theme.error_fg)),
+                );
+            f.render_widget(Clear, area);
+            f.render_widget(dialog, area);
+        }
+    }
+
+    fn render_users(&self, f: &mut Frame, area: Rect) {
+        let summaries = aggregate_by_user(&self.jobs);
+        let header = Row::new(["User", "Running", "Pending", "CPUs", "GPUs", "Memory"])
+            .style(Style::default().fg(self.theme.label_fg));
+        let rows = summaries.iter().map(|s| {
+            Row::new([
+                s.username.clone(),
+                s.running_jobs.to_string(),
+                s.pending_jobs.to_string(),
+                s.total_cpus.to_string(),
+                s.total_gpus.to_string(),
+                format_mem_mb(s.total_mem_mb),
+            ])
+        });
+        let max_user_len = summaries
+            .iter()
+            .map(|s| s.username.len())
+            .max()
+            .unwrap_or(0)
+            .max(4);
+        let widths = [
+            Constraint::Length(max_user_len as u16),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(10),
+        ];
+        let table = Table::new(rows, widths).header(header).block(
+            Block::default()
+                .title(format!("Users ({})", summaries.len()))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(self.theme.border_fg)),
+        );
+        f.render_widget(table, area);
+    }
+
+    fn render_accounts(&self, f: &mut Frame, area: Rect) {
+        let summaries = aggregate_by_account(&self.jobs);
+        let header = Row::new(["Account", "Running", "Pending", "CPU hours", "GPU hours"])
+            .style(Style::default().fg(self.theme.label_fg));
+        let rows = summaries.iter().map(|s| {
+            Row::new([
+                s.account.clone(),
+                s.running_jobs.to_string(),
+                s.pending_jobs.to_string(),
+                format!("{:.1}", s.cpu_hours_running),
+                format!("{:.1}", s.gpu_hours_running),
+            ])
+        });
+        let max_account_len = summaries
+            .iter()
+            .map(|s| s.account.len())
+            .max()
+            .unwrap_or(0)
+            .max(7);
+        let widths = [
+            Constraint::Length(max_account_len as u16),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(10),
+            Constraint::Length(10),
+        ];
+        let table = Table::new(rows, widths).header(header).block(
+            Block::default()
+                .title(format!("Accounts ({})", summaries.len()))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(self.theme.border_fg)),
+        );
+        f.render_widget(table, area);
+    }
+
+    /// Job counts by state, partition usage and the users with the most CPU
+    /// time, without the job list.
+    fn render_overview(&self, f: &mut Frame, area: Rect) {
+        let label = Style::default().fg(self.theme.label_fg);
+        let block = |title: String| {
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(self.theme.border_fg))
+        };
+        let states = count_by_state(&self.jobs);
+        let partitions = aggregate_by_partition(&self.jobs);
+        let mut users = aggregate_by_user(&self.jobs);
+        users.sort_by_key(|u| Reverse(u.cpu_seconds));
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(states.len().max(self.partitions.len()) as u16 + 3),
+                Constraint::Min(3),
+            ])
+            .split(area);
+        let top = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(30), Constraint::Min(0)])
+            .split(rows[0]);
+
+        let state_rows = states.iter().map(|(state, count)| {
+            Row::new([state.clone(), count.to_string()]).style(state_style(&self.theme, state))
+        });
+        let table = Table::new(state_rows, [Constraint::Length(17), Constraint::Length(8)])
+            .header(Row::new(["State", "Jobs"]).style(label))
+            .block(block(format!("Jobs ({})", self.jobs.len())));
+        f.render_widget(table, top[0]);
+
+        let no_jobs = PartitionSummary::default();
+        let usage = |percent: Option<u64>| match percent {
+            Some(p) => Line::from(vec![
+                progress_bar(p as f64 / 100.0, &self.theme),
+                Span::raw(format!(" {:>3}%", p)),
+            ]),
+            None => Line::from(""),
+        };
+        let partition_rows = self.partitions.iter().map(|p| {
+            let summary = partitions
+                .iter()
+                .find(|s| s.partition == p.name)
+                .unwrap_or(&no_jobs);
+            Row::new([
+                Line::from(p.name.as_str()),
+                Line::from(summary.running_jobs.to_string()),
+                usage(summary.cpu_utilization(p)),
+                usage(summary.gpu_utilization(p)),
+            ])
+        });
+        let bar_width = PROGRESS_BAR_WIDTH as u16 + 5;
+        let name_width = self.partitions.iter().map(|p| p.name.len()).max();
+        let table = Table::new(
+            partition_rows,
+            [
+                Constraint::Length(name_width.unwrap_or(0).max(9) as u16),
+                Constraint::Length(7),
+                Constraint::Length(bar_width),
+                Constraint::Length(bar_width),
+            ],
+        )
+        .header(Row::new(["Partition", "Running", "CPUs", "GPUs"]).style(label))
+        .block(block(format!("Partitions ({})", self.partitions.len())));
+        f.render_widget(table, top[1]);
+
+        let user_rows = users.iter().map(|u| {
+            Row::new([
+                u.username.clone(),
+                u.running_jobs.to_string(),
+                u.pending_jobs.to_string(),
+                format!("{:.1}", u.cpu_seconds as f64 / 3600.0),
+                u.total_gpus.to_string(),
+            ])
+        });
+        let name_width = users.iter().map(|u| u.username.len()).max();
+        let table = Table::new(
+            user_rows,
+            [
+                Constraint::Length(name_width.unwrap_or(0).max(4) as u16),
+                Constraint::Length(7),
+                Constraint::Length(7),
+                Constraint::Length(9),
+                Constraint::Length(6),
+            ],
+        )
+        .header(Row::new(["User", "Running", "Pending", "CPU hours", "GPUs"]).style(label))
+        .block(block("Top users by CPU hours of running jobs".to_owned()));
+        f.render_widget(table, rows[1]);
+    }
+
+    /// The jobs that wait for other jobs, below the jobs they wait for.
+    fn render_dependencies(&mut self, f: &mut Frame, area: Rect) {
+        let graph = dependency_graph(&self.jobs);
+        let lines = graph_lines(&graph);
+        let dim = Style::default().add_modifier(Modifier::DIM);
+        let text: Vec<Line> = lines
+            .iter()
+            .map(|line| {
+                let job = &self.jobs[line.job];
+                let style = state_style(&self.theme, &job.state);
+                Line::from(vec![
+                    Span::styled(line.prefix.as_str(), dim),
+                    Span::styled(
+                        line.kind
+                            .as_ref()
+                            .map(|k| k.clone() + " ")
+                            .unwrap_or_default(),
+                        Style::default().fg(self.theme.label_fg),
+                    ),
+                    Span::styled(job.id(), style),
+                    Span::raw(" "),
+                    Span::styled(job.state_compact.as_str(), style),
+                    Span::raw(" "),
+                    Span::raw(job.name.as_str()),
+                    // its dependents are shown further up
+                    Span::styled(if line.repeated { " (*)" } else { "" }, dim),
+                ])
+            })
+            .collect();
+        let text = if text.is_empty() {
+            Text::styled("No job waits for another listed job", dim)
+        } else {
+            Text::from(text)
+        };
+        let max_scroll = lines
+            .len()
+            .saturating_sub(area.height.saturating_sub(2) as usize);
+        self.graph_scroll = self.graph_scroll.min(max_scroll as u16);
+        let paragraph = Paragraph::new(text).scroll((self.graph_scroll, 0)).block(
+            Block::default()
+                .title(format!("Dependencies ({} jobs)", graph.node_count()))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(self.theme.border_fg)),
+        );
+        f.render_widget(paragraph, area);
+    }
+
+    fn render_partitions(&self, f: &mut Frame, area: Rect) {
+        let summaries = aggregate_by_partition(&self.jobs);
+        let no_jobs = PartitionSummary::default();
+        let utilization = |utilization: fn(&PartitionSummary, &Partition) -> Option<u64>| {
+            self.partitions
+                .iter()
+                .filter_map(|p| {
+                    let summary = summaries.iter().find(|s| s.partition == p.name);
+                    utilization(summary.unwrap_or(&no_jobs), p).map(|u| (p.name.as_str(), u))
+                })
+                .collect::<Vec<_>>()
+        };
+        let cpus = utilization(PartitionSummary::cpu_utilization);
+        // partitions without GPUs are left out
+        let gpus = utilization(PartitionSummary::gpu_utilization);
+
+        let areas = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(area);
+        for ((title, data), area) in [("CPUs in use", cpus), ("GPUs in use", gpus)]
+            .into_iter()
+            .zip(areas.iter())
+        {
+            let bars: Vec<Bar> = data
+                .iter()
+                .map(|(name, used)| {
+                    Bar::default()
+                        .label(Line::from(*name))
+                        .value(*used)
+                        .text_value(format!("{}%", used))
+                        .style(Style::default().fg(self.theme.usage_color(*used as f64 / 100.0)))
+                })
+                .collect();
+            let chart = BarChart::default()
+                .block(
+                    Block::default()
+                        .title(title)
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(self.theme.border_fg)),
+                )
+                .data(BarGroup::default().bars(&bars))
+                .direction(Direction::Horizontal)
+                .bar_width(1)
+                .bar_gap(1)
+                .max(100);
+            f.render_widget(chart, *area);
+        }
+    }
+}
+
+/// One node per line, scrolled with the up and down keys while focused.
+fn render_nodes(
+    f: &mut Frame,
+    area: Rect,
+    nodes: &[String],
+    scroll: &mut usize,
+    focused: bool,
+    theme: &Theme,
+) {
+    let block = Block::default()
+        .title(format!("Nodes ({})", nodes.len()))
+        .borders(Borders::ALL)
+        .border_style(if focused {
+            Style::default().fg(theme.border_fg)
+        } else {
+            Style::default()
+        });
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    *scroll = min(*scroll, nodes.len().saturating_sub(inner.height as usize));
+    let hyperlinks = supports_hyperlinks::on(supports_hyperlinks::Stream::Stdout);
+    for (row, node) in (inner.y..inner.bottom()).zip(nodes.iter().skip(*scroll)) {
+        let area = Rect::new(inner.x, row, inner.width, 1);
+        f.render_widget(NodeLink { node, hyperlinks }, area);
+    }
+}
+
+/// Links the node name to `ssh://<node>` with OSC 8 if the terminal supports it.
+struct NodeLink<'a> {
+    node: &'a str,
+    hyperlinks: bool,
+}
+
+impl Widget for NodeLink<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Line::raw(self.node).render(area, buf);
+        if !self.hyperlinks {
+            return;
+        }
+        // ratatui counts the escape sequence towards the width of a cell, so
+        // the link is split into chunks of two characters (which is how wide
+        // each chunk is assumed to be)
+        let chars: Vec<char> = self.node.chars().take(area.width as usize).collect();
+        for (i, chunk) in chars.chunks(2).enumerate() {
+            let link = format!(
+                "\x1B]8;;ssh://{}\x07{}\x1B]8;;\x07",
+                self.node,
+                chunk.iter().collect::<String>()
+            );
+            buf.get_mut(area.x + i as u16 * 2, area.y).set_symbol(&link);
+        }
+    }
+}
+
+/// The index of the first job shown in a list of `rows` lines, moved as
+/// little as possible from `offset` to show the selected job.
+fn scroll_offset(offset: usize, selected: Option<usize>, rows: usize, len: usize) -> usize {
+    // no empty lines at the end after jobs disappeared
+    let offset = offset.min(len.saturating_sub(rows));
+    match selected {
+        Some(i) if i < offset => i,
+        Some(i) if i >= offset + rows => (i + 1).saturating_sub(rows),
+        _ => offset,
+    }
+}
+
+/// The index of the job at a click into the job list drawn in `area`. A click
+/// on the right border jumps to the same relative position in the list, like
+/// a scrollbar.
+fn clicked_job(area: Rect, offset: usize, len: usize, column: u16, row: u16) -> Option<usize> {
+    let inner_top = area.y + 1;
+    let inner_height = area.height.saturating_sub(2);
+    let inside = column >= area.x
+        && column < area.right()
+        && row >= inner_top
+        && row < inner_top + inner_height;
+    if !inside {
+        return None;
+    }
+    let line = (row - inner_top) as usize;
+    let index = if column == area.right() - 1 {
+        line * len / inner_height as usize
+    } else if column > area.x {
+        offset + line
+    } else {
+        return None;
+    };
+    (index < len).then_some(index)
+}
+
+fn centered_lines(percent_x: u16, lines: u16, r: Rect) -> Rect {
+    let dy = r.height.saturating_sub(lines) / 2;
+    let r = Rect::new(r.x, r.y + dy, r.width, min(lines, r.height - dy));
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_x) / 2),
+                Constraint::Percentage(percent_x),
+                Constraint::Percentage((100 - percent_x) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(r)[1]
+}
+
+/// Returns `false` if the notification could not be shown, e.g. over SSH
+/// without a display.
+/// Shows a desktop notification on a thread of its own, D-Bus may be slow to
+/// answer. Rings the bell instead if there is no display or it fails.
+fn desktop_notify(sender: &Sender<AppMessage>, summary: String, body: String) {
+    let has_display = ["DISPLAY", "WAYLAND_DISPLAY"]
+        .iter()
+        .any(|v| std::env::var_os(v).is_some());
+    if !has_display {
+        let _ = sender.send(AppMessage::Bell);
+        return;
+    }
+    let sender = sender.clone();
+    std::thread::spawn(move || {
+        let shown = notify_rust::Notification::new()
+            .appname("turm")
+            .summary(&summary)
+            .body(&body)
+            .show();
+        if shown.is_err() {
+            let _ = sender.send(AppMessage::Bell);
+        }
+    });
+}
+
+const PROGRESS_BAR_WIDTH: usize = 10;
+
+/// Keys that cannot be changed in the config file, for the help popup.
+const FIXED_KEYS: [(&str, &str); 5] = [
+    (
+        "pgup/pgdown",
+        "scroll the log, faster with shift, ctrl or alt",
+    ),
+    ("home/end", "top or bottom of the log"),
+    ("enter", "confirm"),
+    ("esc", "cancel, close dialogs"),
+    ("mouse", "click to select a job, wheel to navigate"),
+];
+
+/// How much `+` and `-` change the width of job names.
+const NAME_WIDTH_STEP: usize = 5;
+
+/// Cuts `s` to at most `width` characters, ending with `…` if it was longer.
+fn truncate(s: &str, width: usize) -> Cow<'_, str> {
+    if s.chars().count() <= width {
+        return Cow::Borrowed(s);
+    }
+    let mut truncated: String = s.chars().take(width.saturating_sub(1)).collect();
+    truncated.push('…');
+    Cow::Owned(truncated)
+}
+
+/// How much of its time limit a job has used, `None` for jobs without a limit.
+fn time_used_fraction(job: &Job) -> Option<f64> {
+    let limit = job.time_limit_seconds().filter(|&l| l > 0)?;
+    let remaining = job.time_remaining_seconds()?;
+    Some(1.0 - remaining as f64 / limit as f64)
+}
+
+/// E.g. `3 hours`, in the largest whole unit.
+fn human_duration(d: TimeDelta) -> String {
+    let units = [
+        (d.num_days(), "day"),
+        (d.num_hours(), "hour"),
+        (d.num_minutes(), "minute"),
+    ];
+    match units.into_iter().find(|(n, _)| *n > 0) {
+        Some((1, unit)) => format!("1 {}", unit),
+        Some((n, unit)) => format!("{} {}s", n, unit),
+        None => "less than a minute".to_owned(),
+    }
+}
+
+/// E.g. `3 hours ago`.
+fn relative_time(ago: TimeDelta) -> String {
+    if ago < TimeDelta::minutes(1) {
+        "just now".to_owned()
+    } else {
+        format!("{} ago", human_duration(ago))
+    }
+}
+
+fn progress_bar(fraction: f64, theme: &Theme) -> Span<'static> {
+    let filled = min(
+        (fraction * PROGRESS_BAR_WIDTH as f64).round() as usize,
+        PROGRESS_BAR_WIDTH,
+    );
+    Span::styled(
+        "▓".repeat(filled) + &"░".repeat(PROGRESS_BAR_WIDTH - filled),
+        Style::default().fg(theme.usage_color(fraction)),
+    )
 }
 
 fn chunked_string(s: &str, first_chunk_size: usize, chunk_size: usize) -> Vec<&str> {
@@ -551,7 +2244,7 @@ fn chunked_string(s: &str, first_chunk_size: usize, chunk_size: usize) -> Vec<&s
         .enumerate()
         .filter(|&(i, _)| {
             if i > (first_chunk_size) {
-                chunk_size > 0 && (i - first_chunk_size) % chunk_size == 0
+                chunk_size > 0 && (i - first_chunk_size).is_multiple_of(chunk_size)
             } else {
                 i == 0 || i == first_chunk_size
             }
@@ -565,51 +2258,6 @@ fn chunked_string(s: &str, first_chunk_size: usize, chunk_size: usize) -> Vec<&s
     iter.chain(once(&s[last_index..])).collect()
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_chunked_string() {
-        // Divisible
-        let input = "abcdefghij";
-        let expected = vec!["abcd", "ef", "gh", "ij"];
-        assert_eq!(chunked_string(input, 4, 2), expected);
-
-        // Not divisible
-        let input = "123456789";
-        let expected = vec!["1234", "56", "78", "9"];
-        assert_eq!(chunked_string(input, 4, 2), expected);
-
-        // Smaller
-        let input = "abc";
-        let expected = vec!["abc"];
-        assert_eq!(chunked_string(input, 4, 2), expected);
-
-        // Smaller
-        let input = "abcde";
-        let expected = vec!["abcd", "e"];
-        assert_eq!(chunked_string(input, 4, 2), expected);
-
-        // Empty
-        let input = "";
-        let expected: Vec<&str> = vec![""];
-        assert_eq!(chunked_string(input, 4, 2), expected);
-
-        let input = "123456789";
-        let expected = vec!["1234", "56789"];
-        assert_eq!(chunked_string(input, 4, 0), expected);
-
-        let input = "123456789";
-        let expected = vec!["12", "34", "56", "78", "9"];
-        assert_eq!(chunked_string(input, 0, 2), expected);
-
-        let input = "123456789";
-        let expected = vec!["123456789"];
-        assert_eq!(chunked_string(input, 0, 0), expected);
-    }
-}
-
 fn fit_text(
     s: &str,
     lines: usize,
@@ -617,8 +2265,8 @@ fn fit_text(
     anchor: ScrollAnchor,
     offset: usize,
     wrap: bool,
-) -> Text {
-    let s = s.rsplit_once(&['\r', '\n']).map_or(s, |(p, _)| p); // skip everything after last line delimiter
+) -> Text<'_> {
+    let s = s.rsplit_once(['\r', '\n']).map_or(s, |(p, _)| p); // skip everything after last line delimiter
     let l = s.lines().flat_map(|l| l.split('\r')); // bandaid for term escape codes
     let iter = match anchor {
         ScrollAnchor::Top => Either::Left(l),
@@ -678,23 +2326,450 @@ fn fit_text(
 }
 
 impl App {
+    /// Live jobs from squeue, followed by finished jobs from sacct. Finished
+    /// jobs keep the output paths squeue reported while they were running.
+    fn merge_jobs(&mut self) {
+        let live: HashSet<String> = self.squeue_jobs.iter().map(|j| j.id()).collect();
+        let finished = self
+            .sacct_jobs
+            .iter()
+            .filter(|j| !live.contains(&j.id()))
+            .cloned()
+            .map(|mut j| {
+                if let Some((stdout, stderr)) = self.output_paths.get(&j.id()) {
+                    j.stdout.clone_from(stdout);
+                    j.stderr.clone_from(stderr);
+                }
+                j
+            });
+        self.jobs = self.squeue_jobs.iter().cloned().chain(finished).collect();
+        if let Some((key, ascending)) = self.sort {
+            sort_jobs(&mut self.jobs, key, ascending);
+        }
+        if let Some(id) = &self.select_on_load {
+            if let Some(i) = self.job_index(id) {
+                self.job_list_state.select(Some(i));
+                self.select_on_load = None;
+            }
+        }
+    }
+
+    /// The position of a job in the filtered list, array jobs by their first
+    /// task.
+    fn job_index(&self, id: &str) -> Option<usize> {
+        self.filter
+            .apply(&self.jobs)
+            .iter()
+            .position(|j| j.id() == id || j.array_id == id)
+    }
+
+    /// Selects the first job the selected job depends on that is still listed.
+    fn select_dependency(&mut self) {
+        let index = self.selected_job().and_then(|j| {
+            j.dependencies()
+                .iter()
+                .flat_map(|d| &d.job_ids)
+                .find_map(|id| self.job_index(id))
+        });
+        match index {
+            Some(i) => self.job_list_state.select(Some(i)),
+            None => {
+                self.notification = Some(Notification::error(
+                    "The job depends on no listed job".to_owned(),
+                ))
+            }
+        }
+    }
+
+    /// Shows only totals instead of the job list.
+    pub fn show_overview(&mut self) {
+        self.tab = Tab::Overview;
+    }
+
+    /// Selects the job with `id` as soon as squeue reports it, e.g. one that
+    /// was just submitted.
+    pub fn select_job(&mut self, id: String) {
+        self.select_on_load = Some(id);
+    }
+
+    fn handle_action(&mut self, action: Action) {
+        match action {
+            Action::Help => self.dialog = Some(Dialog::Help { scroll: 0 }),
+            // the overview has no tabs and no job list
+            _ if self.tab == Tab::Overview => {}
+            Action::JobsTab => self.tab = Tab::Jobs,
+            Action::UsersTab => self.tab = Tab::Users,
+            Action::PartitionsTab => self.tab = Tab::Partitions,
+            Action::DependenciesTab => self.tab = Tab::Dependencies,
+            Action::AccountsTab => self.tab = Tab::Accounts,
+            Action::Up if self.tab == Tab::Dependencies => {
+                self.graph_scroll = self.graph_scroll.saturating_sub(1)
+            }
+            Action::Down if self.tab == Tab::Dependencies => {
+                self.graph_scroll = self.graph_scroll.saturating_add(1)
+            }
+            // the other actions act on the job list
+            _ if self.tab != Tab::Jobs => {}
+            Action::FocusPrevious => self.focus_previous_panel(),
+            Action::FocusNext => self.focus_next_panel(),
+            Action::Up => match self.focus {
+                Focus::Jobs => {
+                    self.select_previous_job();
+                    self.node_scroll = 0;
+                }
+                Focus::Nodes => self.node_scroll = self.node_scroll.saturating_sub(1),
+            },
+            Action::Down => match self.focus {
+                Focus::Jobs => {
+                    self.select_next_job();
+                    self.node_scroll = 0;
+                }
+                // clamped when rendered
+                Focus::Nodes => self.node_scroll += 1,
+            },
+            Action::NextPage => self.page_jobs(true),
+            Action::PreviousPage => self.page_jobs(false),
+            Action::CancelJob => {
+                if let Some(id) = self.selected_job().map(|j| j.id()) {
+                    self.dialog = Some(Dialog::ConfirmCancelJob(id));
+                }
+            }
+            Action::Details => {
+                if let Some(id) = self.selected_job().map(|j| j.id()) {
+                    self.dialog = Some(Dialog::LoadingJobDetail(id.clone()));
+                    let scontrol = self.scontrol.clone();
+                    let sender = self.sender.clone();
+                    std::thread::spawn(move || {
+                        let detail = JobDetail::fetch(&scontrol, &id);
+                        sender.send(AppMessage::JobDetail(id, detail))
+                    });
+                }
+            }
+            Action::HoldJob => {
+                if let Some(id) = self.selected_pending_job_id() {
+                    self.handle(AppMessage::HoldJob(id));
+                }
+            }
+            Action::ReleaseJob => {
+                if let Some(id) = self.selected_pending_job_id() {
+                    self.handle(AppMessage::ReleaseJob(id));
+                }
+            }
+            Action::SwitchOutput => {
+                self.output_file_view = match self.output_file_view {
+                    OutputFileView::Stdout => OutputFileView::Stderr,
+                    OutputFileView::Stderr => OutputFileView::Stdout,
+                };
+            }
+            Action::Wrap => {
+                self.job_output_wrap = !self.job_output_wrap;
+            }
+            Action::WiderName => {
+                self.name_width += NAME_WIDTH_STEP;
+            }
+            Action::Dependency => self.select_dependency(),
+            Action::NarrowerName => {
+                self.name_width = self
+                    .name_width
+                    .saturating_sub(NAME_WIDTH_STEP)
+                    .max(NAME_WIDTH_STEP);
+            }
+            Action::ToggleLog => {
+                self.show_log = !self.show_log;
+            }
+            Action::Sort => {
+                self.sort_pending = true;
+            }
+            Action::Columns => {
+                self.dialog = Some(Dialog::ColumnPicker { selected: 0 });
+            }
+            Action::Filter => {
+                let pattern = self
+                    .filter
+                    .name_regex
+                    .as_ref()
+                    .map(|r| r.as_str().to_owned())
+                    .unwrap_or_default();
+                self.filter_input = Some((pattern, None));
+            }
+            Action::Ssh => {
+                if let Some(job) = self.selected_running_job() {
+                    match job.nodes().first() {
+                        Some(node) => {
+                            let mut command = Command::new("ssh");
+                            command.arg(node);
+                            self.foreground_command = Some(command);
+                        }
+                        None => {
+                            self.notification = Some(Notification::error(format!(
+                                "Job {} has no nodes yet",
+                                job.id()
+                            )))
+                        }
+                    }
+                }
+            }
+            Action::Refresh => {
+                if let Some(watcher) = self
+                    .job_watcher
+                    .as_ref()
+                    .filter(|_| self.watcher_died.is_none())
+                {
+                    self.refreshing = true;
+                    watcher.poll_now();
+                }
+            }
+            Action::CopyId => {
+                if let Some(id) = self.selected_job().map(|j| j.id()) {
+                    self.copy_to_clipboard(id);
+                }
+            }
+            Action::Export => self.export_snapshot(),
+            Action::Attach => {
+                if let Some(job) = self.selected_running_job() {
+                    // the first step, array tasks as `<array job>_<task>.0`
+                    let mut command = Command::new("sattach");
+                    command.arg(format!("{}.0", job.id()));
+                    self.foreground_command = Some(command);
+                }
+            }
+            // handled by the run loop and the column picker
+            Action::Quit | Action::MoveColumnUp | Action::MoveColumnDown | Action::ToggleColumn => {
+            }
+        }
+    }
+
+    fn copy_to_clipboard(&mut self, text: String) {
+        if self.clipboard.is_none() {
+            self.clipboard = Clipboard::new().ok();
+        }
+        let copied = self
+            .clipboard
+            .as_mut()
+            .is_some_and(|c| c.set_text(&text).is_ok());
+        self.notification = Some(if copied {
+            Notification::info(format!("Copied {}", text))
+        } else {
+            // e.g. over ssh without X forwarding
+            Notification::error(format!("No clipboard available: {}", text))
+        });
+    }
+
+    /// Writes the listed jobs like `--output json` to
+    /// `~/turm-snapshot-<time>.json`, e.g. for `turm diff`.
+    fn export_snapshot(&mut self) {
+        let dir = env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+        let path = dir.join(format!(
+            "turm-snapshot-{}.json",
+            Local::now().format("%Y%m%dT%H%M%S")
+        ));
+        let written = File::create(&path).and_then(|mut file| {
+            serde_json::to_writer_pretty(&mut file, &self.filter.apply(&self.jobs))?;
+            writeln!(file)
+        });
+        self.notification = Some(match written {
+            Ok(()) => Notification::info(format!("Exported to {}", path.display())),
+            Err(e) => Notification::error(format!("Failed to export to {}: {}", path.display(), e)),
+        });
+    }
+
+    /// Remembers the columns and sort order for the next start.
+    fn save_view(&mut self) {
+        if let Err(e) = Config::save_view(&self.columns, self.sort) {
+            self.notification = Some(Notification::error(format!(
+                "Failed to save the config: {}",
+                e
+            )));
+        }
+    }
+
+    fn job_output(&self) -> &JobOutput {
+        match self.output_file_view {
+            OutputFileView::Stdout => &self.job_stdout,
+            OutputFileView::Stderr => &self.job_stderr,
+        }
+    }
+
+    fn job_output_mut(&mut self) -> &mut JobOutput {
+        match self.output_file_view {
+            OutputFileView::Stdout => &mut self.job_stdout,
+            OutputFileView::Stderr => &mut self.job_stderr,
+        }
+    }
+
+    /// The selected job out of the jobs matching the filter.
+    fn selected_job(&self) -> Option<&Job> {
+        self.job_list_state
+            .selected()
+            .and_then(|i| self.filter.apply(&self.jobs).get(i).copied())
+    }
+
+    /// Edits the name filter, which is applied on every keystroke. Enter keeps
+    /// the filter, Esc removes it.
+    fn handle_filter_input(&mut self, key: KeyEvent) {
+        let Some((pattern, error)) = &mut self.filter_input else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.filter_input = None;
+                self.filter.name_regex = None;
+                return;
+            }
+            KeyCode::Enter => {
+                if error.is_none() {
+                    self.filter_input = None;
+                }
+                return;
+            }
+            KeyCode::Backspace => {
+                pattern.pop();
+            }
+            KeyCode::Char(c) => pattern.push(c),
+            _ => return,
+        }
+
+        if pattern.is_empty() {
+            self.filter.name_regex = None;
+            *error = None;
+        } else {
+            match Regex::new(pattern) {
+                Ok(regex) => {
+                    self.filter.name_regex = Some(regex);
+                    *error = None;
+                }
+                // the last line holds the reason, the ones before quote the pattern
+                Err(e) => *error = e.to_string().lines().last().map(str::to_owned),
+            }
+        }
+        self.job_list_state.select(Some(0));
+    }
+
+    /// The selected job if it is running, otherwise shows an error.
+    fn selected_running_job(&mut self) -> Option<Job> {
+        let job = self.selected_job()?.clone();
+        if !job.is_running() {
+            self.notification = Some(Notification::error(format!(
+                "Job {} is not running",
+                job.id()
+            )));
+            return None;
+        }
+        Some(job)
+    }
+
+    fn selected_pending_job_id(&self) -> Option<String> {
+        self.selected_job()
+            .filter(|j| j.is_pending())
+            .map(|j| j.id())
+    }
+
+    /// Notifies about a job entering one of the `notify_on` states, with a
+    /// desktop notification or else the terminal bell.
+    fn notify_state_change(&mut self, id: &str, old_state: &str, new_state: &str) {
+        if !self
+            .notify_on
+            .iter()
+            .any(|s| s.eq_ignore_ascii_case(new_state))
+        {
+            return;
+        }
+        let name = self
+            .jobs
+            .iter()
+            .find(|j| j.id() == id)
+            .map(|j| j.name.as_str())
+            .unwrap_or_default();
+        let message = format!("Job {} ({}) {} → {}", id, name, old_state, new_state);
+        desktop_notify(
+            &self.sender,
+            format!("Job {} {}", id, new_state),
+            message.clone(),
+        );
+        self.notification = Some(match new_state {
+            "COMPLETED" => Notification::info(message),
+            _ => Notification::error(message),
+        });
+    }
+
+    /// Runs a Slurm command on a job, reports the outcome as a notification
+    /// and refreshes the job list.
+    fn run_job_command(&mut self, program: &Path, args: &[&str], success: String) {
+        tracing::info!(program = %program.display(), ?args, "running job command");
+        // slurmctld may take a while to answer, the TUI keeps running
+        let mut command = Command::new(program);
+        command.args(args);
+        let program = program.to_owned();
+        let sender = self.sender.clone();
+        std::thread::spawn(move || {
+            let result = match command.output() {
+                Ok(output) if output.status.success() => Ok(success),
+                Ok(output) => Err(format!(
+                    "{} failed: {}",
+                    program.display(),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )),
+                Err(e) => Err(format!("Failed to execute {}: {}", program.display(), e)),
+            };
+            sender.send(AppMessage::JobCommandDone(result))
+        });
+    }
+
     fn focus_next_panel(&mut self) {
         match self.focus {
-            Focus::Jobs => self.focus = Focus::Jobs,
+            Focus::Jobs if self.selected_job().is_some_and(|j| j.node_count() > 0) => {
+                self.focus = Focus::Nodes
+            }
+            Focus::Jobs | Focus::Nodes => {}
         }
     }
 
     fn focus_previous_panel(&mut self) {
         match self.focus {
-            Focus::Jobs => self.focus = Focus::Jobs,
+            Focus::Jobs | Focus::Nodes => self.focus = Focus::Jobs,
+        }
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if self.tab != Tab::Jobs || self.dialog.is_some() || self.filter_input.is_some() {
+            return;
+        }
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let link = self.dependency_links.iter().find(|(area, _)| {
+                    area.contains(layout::Position {
+                        x: mouse.column,
+                        y: mouse.row,
+                    })
+                });
+                if let Some(i) = link.and_then(|(_, id)| self.job_index(id)) {
+                    self.job_list_state.select(Some(i));
+                    return;
+                }
+                let len = self.filter.apply(&self.jobs).len();
+                if let Some(i) = clicked_job(
+                    self.job_list_area,
+                    self.job_list_state.offset(),
+                    len,
+                    mouse.column,
+                    mouse.row,
+                ) {
+                    self.job_list_state.select(Some(i));
+                    self.focus = Focus::Jobs;
+                }
+            }
+            MouseEventKind::ScrollDown => self.select_next_job(),
+            MouseEventKind::ScrollUp => self.select_previous_job(),
+            _ => {}
         }
     }
 
     fn select_next_job(&mut self) {
+        let len = self.filter.apply(&self.jobs).len();
         let i = match self.job_list_state.selected() {
             Some(i) => {
-                if i >= self.jobs.len() - 1 {
-                    self.jobs.len() - 1
+                if i >= len.saturating_sub(1) {
+                    len.saturating_sub(1)
                 } else {
                     i + 1
                 }
@@ -704,6 +2779,20 @@ impl App {
         self.job_list_state.select(Some(i));
     }
 
+    /// Moves the selection by the number of jobs that fit in the list.
+    fn page_jobs(&mut self, down: bool) {
+        let page = usize::from(self.job_list_area.height.saturating_sub(2)).max(1);
+        let len = self.filter.apply(&self.jobs).len();
+        let i = self.job_list_state.selected().unwrap_or(0);
+        let i = if down {
+            min(i + page, len.saturating_sub(1))
+        } else {
+            i.saturating_sub(page)
+        };
+        self.job_list_state.select(Some(i));
+        self.node_scroll = 0;
+    }
+
     fn select_previous_job(&mut self) {
         let i = match self.job_list_state.selected() {
             Some(i) => {
@@ -718,3 +2807,221 @@ impl App {
         self.job_list_state.select(Some(i));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+    use ratatui::style::Color;
+
+    #[test]
+    fn test_relative_time() {
+        assert_eq!(relative_time(TimeDelta::seconds(30)), "just now");
+        assert_eq!(relative_time(TimeDelta::seconds(-30)), "just now");
+        assert_eq!(relative_time(TimeDelta::minutes(1)), "1 minute ago");
+        assert_eq!(relative_time(TimeDelta::minutes(185)), "3 hours ago");
+        assert_eq!(relative_time(TimeDelta::days(2)), "2 days ago");
+        assert_eq!(human_duration(TimeDelta::seconds(5)), "less than a minute");
+    }
+
+    #[test]
+    fn test_progress_bar() {
+        let theme = Theme::default();
+        assert_eq!(progress_bar(0.0, &theme).content, "░░░░░░░░░░");
+        assert_eq!(progress_bar(0.5, &theme).content, "▓▓▓▓▓░░░░░");
+        assert_eq!(progress_bar(0.5, &theme).style.fg, Some(Color::Green));
+        assert_eq!(progress_bar(0.8, &theme).style.fg, Some(Color::Yellow));
+        assert_eq!(progress_bar(0.95, &theme).style.fg, Some(Color::Red));
+        assert_eq!(progress_bar(1.5, &theme).content, "▓▓▓▓▓▓▓▓▓▓");
+    }
+
+    #[test]
+    fn test_chunked_string() {
+        // Divisible
+        let input = "abcdefghij";
+        let expected = vec!["abcd", "ef", "gh", "ij"];
+        assert_eq!(chunked_string(input, 4, 2), expected);
+
+        // Not divisible
+        let input = "123456789";
+        let expected = vec!["1234", "56", "78", "9"];
+        assert_eq!(chunked_string(input, 4, 2), expected);
+
+        // Smaller
+        let input = "abc";
+        let expected = vec!["abc"];
+        assert_eq!(chunked_string(input, 4, 2), expected);
+
+        // Smaller
+        let input = "abcde";
+        let expected = vec!["abcd", "e"];
+        assert_eq!(chunked_string(input, 4, 2), expected);
+
+        // Empty
+        let input = "";
+        let expected: Vec<&str> = vec![""];
+        assert_eq!(chunked_string(input, 4, 2), expected);
+
+        let input = "123456789";
+        let expected = vec!["1234", "56789"];
+        assert_eq!(chunked_string(input, 4, 0), expected);
+
+        let input = "123456789";
+        let expected = vec!["12", "34", "56", "78", "9"];
+        assert_eq!(chunked_string(input, 0, 2), expected);
+
+        let input = "123456789";
+        let expected = vec!["123456789"];
+        assert_eq!(chunked_string(input, 0, 0), expected);
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("train", 10), "train");
+        assert_eq!(truncate("train", 5), "train");
+        assert_eq!(truncate("train-resnet", 5), "trai…");
+        assert_eq!(truncate("préparation", 4), "pré…");
+    }
+
+    #[test]
+    fn test_scroll_offset() {
+        assert_eq!(scroll_offset(0, Some(5), 10, 100), 0);
+        assert_eq!(scroll_offset(0, Some(10), 10, 100), 1);
+        assert_eq!(scroll_offset(20, Some(25), 10, 100), 20);
+        assert_eq!(scroll_offset(20, Some(15), 10, 100), 15);
+        assert_eq!(scroll_offset(20, None, 10, 100), 20);
+        // the list got shorter
+        assert_eq!(scroll_offset(20, Some(12), 10, 15), 5);
+        assert_eq!(scroll_offset(20, Some(0), 10, 5), 0);
+    }
+
+    #[test]
+    fn test_clicked_job() {
+        let area = Rect::new(0, 0, 20, 12);
+        // the first row inside the border
+        assert_eq!(clicked_job(area, 0, 30, 5, 1), Some(0));
+        assert_eq!(clicked_job(area, 10, 30, 5, 3), Some(12));
+        assert_eq!(clicked_job(area, 0, 5, 5, 8), None);
+        assert_eq!(clicked_job(area, 0, 30, 5, 0), None);
+        assert_eq!(clicked_job(area, 0, 30, 25, 1), None);
+        // on the right border, halfway down
+        assert_eq!(clicked_job(area, 0, 30, 19, 6), Some(15));
+    }
+
+    /// Drawing the TUI while the selection moves through 5000 jobs, run with
+    /// `cargo test --release test_draw_many_jobs -- --ignored`.
+    #[test]
+    #[ignore = "benchmark"]
+    fn test_draw_many_jobs() {
+        let jobs = (0..5000)
+            .map(|i| Job {
+                job_id: (10000 + i).to_string(),
+                array_id: (10000 + i).to_string(),
+                name: format!("job-{}", i),
+                state: ["RUNNING", "PENDING"][i % 2].to_owned(),
+                state_compact: ["R", "PD"][i % 2].to_owned(),
+                ..Job::test_default()
+            })
+            .collect();
+        let mut app = replay_app(jobs);
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(160, 40)).unwrap();
+
+        let start = Instant::now();
+        for _ in 0..200 {
+            app.handle_action(Action::Down);
+            terminal.draw(|f| app.ui(f)).unwrap();
+        }
+        assert_eq!(app.job_list_state.selected(), Some(200));
+        // about 0.2s on a laptop, a frame every 5ms at most
+        let elapsed = start.elapsed();
+        assert!(elapsed < Duration::from_secs(1), "took {:?}", elapsed);
+    }
+
+    fn replay_app(jobs: Vec<Job>) -> App {
+        let (_, input) = unbounded();
+        App::new(
+            input,
+            Arc::new(AtomicBool::new(false)),
+            AppConfig {
+                slurm_refresh_rate: 2,
+                file_refresh_rate: 2,
+                job_watcher: JobWatcherConfig::default(),
+                notify_on: Vec::new(),
+                columns: ColumnSet::default(),
+                sort: None,
+                name_width: 30,
+                gpu_warning_after: Duration::from_secs(300),
+                keybindings: Keybindings::default(),
+                theme: Theme::default(),
+                feed: JobFeed::Replay(Snapshot {
+                    jobs,
+                    taken: Local::now(),
+                }),
+            },
+        )
+    }
+
+    #[test]
+    fn test_page_jobs() {
+        let jobs = (0..25)
+            .map(|i| Job {
+                job_id: i.to_string(),
+                ..Job::test_default()
+            })
+            .collect();
+        let mut app = replay_app(jobs);
+        // 10 jobs fit inside the border
+        app.job_list_area = Rect::new(0, 0, 40, 12);
+        let key = |code, modifiers| AppMessage::Key(KeyEvent::new(code, modifiers));
+
+        app.handle(key(KeyCode::Char('f'), KeyModifiers::NONE));
+        assert_eq!(app.job_list_state.selected(), Some(10));
+        // scrolls the log, also with the log hidden
+        app.show_log = false;
+        app.handle(key(KeyCode::PageUp, KeyModifiers::CONTROL));
+        assert_eq!(app.job_list_state.selected(), Some(10));
+        assert_eq!(app.job_stdout.offset, 50);
+
+        app.handle_action(Action::NextPage);
+        app.handle_action(Action::NextPage);
+        assert_eq!(app.job_list_state.selected(), Some(24));
+        app.handle_action(Action::PreviousPage);
+        assert_eq!(app.job_list_state.selected(), Some(14));
+        app.handle(key(KeyCode::Char('b'), KeyModifiers::NONE));
+        assert_eq!(app.job_list_state.selected(), Some(4));
+        app.handle_action(Action::PreviousPage);
+        assert_eq!(app.job_list_state.selected(), Some(0));
+    }
+
+    #[test]
+    fn test_job_detail_is_fetched_in_background() {
+        let mut app = replay_app(vec![Job::test_default()]);
+        app.scontrol = PathBuf::from("/nonexistent/scontrol");
+        let next_detail = |app: &App| loop {
+            match app.receiver.recv_timeout(Duration::from_secs(5)) {
+                Ok(message @ AppMessage::JobDetail(..)) => break message,
+                Ok(_) => {}
+                Err(e) => panic!("no job detail: {}", e),
+            }
+        };
+
+        app.handle_action(Action::Details);
+        assert!(matches!(&app.dialog, Some(Dialog::LoadingJobDetail(_))));
+        let message = next_detail(&app);
+        app.handle(message);
+        assert!(app.dialog.is_none());
+        assert!(app.notification.as_ref().is_some_and(|n| n.is_error));
+
+        // closed before scontrol answered
+        app.notification = None;
+        app.handle_action(Action::Details);
+        app.handle(AppMessage::Key(KeyEvent::new(
+            KeyCode::Esc,
+            KeyModifiers::NONE,
+        )));
+        let message = next_detail(&app);
+        app.handle(message);
+        assert!(app.dialog.is_none());
+        assert!(app.notification.is_none());
+    }
+}
@@ -0,0 +1,78 @@
+use std::{env, path::Path, process::Command};
+
+/// The name of the Slurm cluster from `scontrol show config`, or
+/// `$SLURM_CLUSTER_NAME`, or `?` if neither is known.
+pub fn cluster_name(scontrol: &Path) -> String {
+    Command::new(scontrol)
+        .args(["show", "config"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| parse_cluster_name(&String::from_utf8_lossy(&o.stdout)))
+        .or_else(|| {
+            env::var("SLURM_CLUSTER_NAME")
+                .ok()
+                .filter(|n| !n.is_empty())
+        })
+        .unwrap_or_else(|| "?".to_owned())
+}
+
+/// The version printed by `squeue --version`, e.g. `23.11.0`.
+pub fn slurm_version(squeue: &Path) -> Option<String> {
+    Command::new(squeue)
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| parse_slurm_version(&String::from_utf8_lossy(&o.stdout)))
+}
+
+/// `slurm 23.11.0` or, with some distributions, `slurm-wlm 23.11.0`.
+fn parse_slurm_version(output: &str) -> Option<String> {
+    let mut words = output.split_whitespace();
+    words
+        .next()
+        .filter(|w| w.starts_with("slurm"))
+        .and(words.next())
+        .map(str::to_owned)
+}
+
+/// Finds the `ClusterName = name` line.
+fn parse_cluster_name(config: &str) -> Option<String> {
+    config.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        (key.trim() == "ClusterName")
+            .then(|| value.trim().to_owned())
+            .filter(|v| !v.is_empty())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cluster_name() {
+        let config = "Configuration data as of 2024-07-28T10:00:00\n\
+                      AccountingStorageType   = accounting_storage/slurmdbd\n\
+                      ClusterName             = hpc1\n\
+                      SlurmctldPort           = 6817\n";
+        assert_eq!(parse_cluster_name(config), Some("hpc1".to_owned()));
+        assert_eq!(parse_cluster_name("ClusterName = \n"), None);
+        assert_eq!(parse_cluster_name(""), None);
+    }
+
+    #[test]
+    fn test_parse_slurm_version() {
+        assert_eq!(
+            parse_slurm_version("slurm 23.11.0\n"),
+            Some("23.11.0".to_owned())
+        );
+        assert_eq!(
+            parse_slurm_version("slurm-wlm 21.08.5\n"),
+            Some("21.08.5".to_owned())
+        );
+        assert_eq!(parse_slurm_version("squeue: unrecognized option\n"), None);
+        assert_eq!(parse_slurm_version(""), None);
+    }
+}
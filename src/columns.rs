@@ -0,0 +1,215 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Column {
+    State,
+    JobId,
+    Cluster,
+    Partition,
+    User,
+    Account,
+    Time,
+    TimeLimit,
+    Priority,
+    Progress,
+    GpuWarning,
+    Name,
+}
+
+impl Column {
+    pub fn name(&self) -> &str {
+        match self {
+            Column::State => "state",
+            Column::JobId => "id",
+            Column::Cluster => "cluster",
+            Column::Partition => "partition",
+            Column::User => "user",
+            Column::Account => "account",
+            Column::Time => "time",
+            Column::TimeLimit => "time limit",
+            Column::Priority => "priority",
+            Column::Progress => "progress",
+            Column::GpuWarning => "gpu warning",
+            Column::Name => "name",
+        }
+    }
+
+    /// Columns that cannot be hidden.
+    pub fn is_required(&self) -> bool {
+        matches!(self, Column::State | Column::JobId)
+    }
+}
+
+/// The columns of the job list in display order, and whether they are shown.
+///
+/// Serialized as the list of visible columns.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(from = "Vec<Column>", into = "Vec<Column>")]
+pub struct ColumnSet {
+    columns: Vec<(Column, bool)>,
+}
+
+impl Default for ColumnSet {
+    fn default() -> Self {
+        Self {
+            columns: vec![
+                (Column::State, true),
+                (Column::JobId, true),
+                (Column::Cluster, false),
+                (Column::Partition, true),
+                (Column::User, true),
+                (Column::Account, false),
+                (Column::Time, true),
+                (Column::TimeLimit, false),
+                (Column::Priority, false),
+                (Column::Progress, true),
+                (Column::GpuWarning, true),
+                (Column::Name, true),
+            ],
+        }
+    }
+}
+
+impl From<Vec<Column>> for ColumnSet {
+    /// Required columns that are not listed are shown first, all other
+    /// columns that are not listed are hidden.
+    fn from(visible: Vec<Column>) -> Self {
+        let mut columns: Vec<(Column, bool)> = Vec::new();
+        let all = Self::default().columns;
+        let required = all
+            .iter()
+            .map(|(c, _)| *c)
+            .filter(|c| c.is_required() && !visible.contains(c))
+            .collect::<Vec<_>>();
+        for column in required.into_iter().chain(visible) {
+            if !columns.iter().any(|(c, _)| *c == column) {
+                columns.push((column, true));
+            }
+        }
+        for (column, _) in all {
+            if !columns.iter().any(|(c, _)| *c == column) {
+                columns.push((column, false));
+            }
+        }
+        Self { columns }
+    }
+}
+
+impl From<ColumnSet> for Vec<Column> {
+    fn from(columns: ColumnSet) -> Self {
+        columns.visible().collect()
+    }
+}
+
+impl ColumnSet {
+    pub fn all(&self) -> &[(Column, bool)] {
+        &self.columns
+    }
+
+    pub fn visible(&self) -> impl Iterator<Item = Column> + '_ {
+        self.columns.iter().filter(|(_, v)| *v).map(|(c, _)| *c)
+    }
+
+    /// Shows a hidden column after the one it follows by default.
+    pub fn show(&mut self, column: Column) {
+        match self.columns.iter().position(|(c, _)| *c == column) {
+            Some(index) if !self.columns[index].1 => self.columns.remove(index),
+            _ => return,
+        };
+        let default = Self::default().columns;
+        let index = default
+            .iter()
+            .take_while(|(c, _)| *c != column)
+            .last()
+            .and_then(|(prev, _)| self.columns.iter().position(|(c, _)| c == prev))
+            .map_or(0, |i| i + 1);
+        self.columns.insert(index, (column, true));
+    }
+
+    /// Shows or hides the column at `index`, required columns stay visible.
+    pub fn toggle(&mut self, index: usize) {
+        if let Some((column, visible)) = self.columns.get_mut(index) {
+            if !column.is_required() {
+                *visible = !*visible;
+            }
+        }
+    }
+
+    /// Swaps the column at `index` with the one before it and returns its new
+    /// index.
+    pub fn move_up(&mut self, index: usize) -> usize {
+        if index == 0 || index >= self.columns.len() {
+            return index;
+        }
+        self.columns.swap(index, index - 1);
+        index - 1
+    }
+
+    /// Swaps the column at `index` with the one after it and returns its new
+    /// index.
+    pub fn move_down(&mut self, index: usize) -> usize {
+        if index + 1 >= self.columns.len() {
+            return index;
+        }
+        self.columns.swap(index, index + 1);
+        index + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_set() {
+        let mut columns = ColumnSet::default();
+        assert!(!columns.visible().any(|c| c == Column::TimeLimit));
+
+        columns.toggle(7);
+        assert!(columns.visible().any(|c| c == Column::TimeLimit));
+
+        // required columns stay visible
+        columns.toggle(0);
+        columns.toggle(1);
+        assert_eq!(
+            columns.visible().take(2).collect::<Vec<_>>(),
+            [Column::State, Column::JobId]
+        );
+
+        assert_eq!(columns.move_up(1), 0);
+        assert_eq!(columns.all()[0].0, Column::JobId);
+        assert_eq!(columns.move_up(0), 0);
+        assert_eq!(columns.move_down(11), 11);
+        assert_eq!(columns.move_down(0), 1);
+        assert_eq!(columns.all()[1].0, Column::JobId);
+    }
+
+    #[test]
+    fn test_column_set_from_visible() {
+        let columns = ColumnSet::from(vec![Column::Name, Column::JobId, Column::Name]);
+        assert_eq!(
+            columns.visible().collect::<Vec<_>>(),
+            [Column::State, Column::Name, Column::JobId]
+        );
+        assert_eq!(columns.all().len(), ColumnSet::default().all().len());
+
+        // hidden columns are not serialized
+        let default = ColumnSet::default();
+        let visible: Vec<Column> = default.clone().into();
+        assert!(ColumnSet::from(visible.clone()).visible().eq(visible));
+
+        let mut columns = ColumnSet::from(vec![Column::JobId, Column::User, Column::Name]);
+        columns.show(Column::Cluster);
+        assert_eq!(
+            columns.visible().collect::<Vec<_>>(),
+            [
+                Column::State,
+                Column::JobId,
+                Column::Cluster,
+                Column::User,
+                Column::Name
+            ]
+        );
+    }
+}
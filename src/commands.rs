@@ -0,0 +1,259 @@
+use std::{
+    env,
+    io::{self, Write},
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, Instant},
+};
+
+use chrono::Local;
+use crossbeam::channel::unbounded;
+
+use crate::app::AppMessage;
+use crate::diff::apply_changes;
+use crate::filter::JobFilter;
+use crate::job::{parse_job_id, Job, JobState};
+use crate::job_watcher::{JobWatcherConfig, JobWatcherHandle};
+use crate::list;
+use crate::sacct_watcher::sacct_states;
+
+/// A program for `--squeue-binary` and the like: a path to an executable
+/// file, or a name that is looked up in `$PATH`.
+pub fn parse_executable(s: &str) -> Result<PathBuf, String> {
+    let is_executable = |p: &Path| {
+        p.metadata()
+            .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+    };
+    let path = PathBuf::from(s);
+    if path.components().count() > 1 {
+        return match is_executable(&path) {
+            true => Ok(path),
+            false => Err(format!("`{}` is not an executable file", s)),
+        };
+    }
+    let in_path = env::var_os("PATH")
+        .is_some_and(|paths| env::split_paths(&paths).any(|dir| is_executable(&dir.join(s))));
+    match in_path {
+        true => Ok(path),
+        false => Err(format!("`{}` was not found in $PATH", s)),
+    }
+}
+
+/// Cancels the jobs one by one and reports on each. Returns whether all of
+/// them were cancelled.
+pub fn kill(scancel: &Path, job_ids: &[String]) -> bool {
+    let mut all_cancelled = true;
+    for id in job_ids {
+        match Command::new(scancel).arg(id).output() {
+            Ok(output) if output.status.success() => println!("Cancelled job {}", id),
+            Ok(output) => {
+                all_cancelled = false;
+                eprintln!(
+                    "Failed to cancel job {}: {}",
+                    id,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            Err(e) => {
+                eprintln!("Failed to execute {}: {}", scancel.display(), e);
+                return false;
+            }
+        }
+    }
+    all_cancelled
+}
+
+/// Submits `script` with sbatch and returns the IDs of the new jobs.
+pub fn submit(script: &Path, sbatch_args: &[String]) -> Result<Vec<String>, String> {
+    let output = Command::new("sbatch")
+        .arg("--parsable")
+        .args(sbatch_args)
+        .arg(script)
+        .output()
+        .map_err(|e| format!("Failed to execute sbatch: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "sbatch failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let ids = parse_sbatch_output(&String::from_utf8_lossy(&output.stdout));
+    if ids.is_empty() {
+        return Err("sbatch did not print a job ID".to_owned());
+    }
+    Ok(ids)
+}
+
+/// `sbatch --parsable` prints `<jobid>[;<cluster>]`, one line per job.
+fn parse_sbatch_output(stdout: &str) -> Vec<String> {
+    stdout
+        .lines()
+        .filter_map(|l| l.split(';').next())
+        .map(str::trim)
+        .filter(|id| parse_job_id(id).is_ok())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Waits until the job, or all tasks of an array job, finished and returns
+/// the exit code for `turm wait`: 0 if it completed, 1 if it ended in any
+/// other state and 2 if its state is unknown.
+pub fn wait(job_id: &str, config: JobWatcherConfig) -> i32 {
+    let start = Instant::now();
+    let (sender, receiver) = unbounded();
+    let config = config.squeue_args(vec![format!("--jobs={}", job_id)]);
+    let mut watcher = JobWatcherHandle::new(sender, config);
+
+    let mut jobs = Vec::new();
+    let states_in = |jobs: &[Job]| {
+        let states: Vec<String> = jobs
+            .iter()
+            .filter(|j| j.id() == job_id || j.array_id == job_id)
+            .map(|j| j.state.clone())
+            .collect();
+        if states.is_empty() {
+            // finished jobs soon leave the queue and are unknown to squeue
+            // afterwards, sacct still knows them
+            states_of(job_id)
+        } else {
+            states
+        }
+    };
+    let states = loop {
+        let states = match receiver.recv() {
+            Ok(AppMessage::Jobs(new_jobs)) => {
+                jobs = new_jobs;
+                states_in(&jobs)
+            }
+            Ok(AppMessage::JobsChanged(changes)) => {
+                apply_changes(&mut jobs, changes);
+                states_in(&jobs)
+            }
+            // the job may have finished while squeue was failing, the
+            // watcher retries it
+            Ok(AppMessage::SqueueError(_)) => match states_of(job_id) {
+                states if states.is_empty() => continue,
+                states => states,
+            },
+            Ok(AppMessage::WatcherDied(e)) => {
+                eprintln!("{}", e);
+                return 2;
+            }
+            Ok(_) => continue,
+            Err(_) => return 2,
+        };
+        if !states.iter().any(|s| JobState::new(s).is_active()) {
+            break states;
+        }
+    };
+    watcher.shutdown();
+    if states.is_empty() {
+        eprintln!("Job {} not found", job_id);
+        return 2;
+    }
+
+    // the first state that is not COMPLETED, if any
+    let state = states
+        .iter()
+        .find(|s| *s != "COMPLETED")
+        .unwrap_or(&states[0]);
+    eprintln!(
+        "Job {} {} after {}",
+        job_id,
+        state,
+        format_elapsed(start.elapsed())
+    );
+    match state.as_str() {
+        "COMPLETED" => 0,
+        _ => 1,
+    }
+}
+
+/// For `--no-tui`: prints the job list whenever the watcher fetched it, on
+/// a cleared screen like `watch squeue`. Only returns if the watcher gives
+/// up, with exit code 1.
+pub fn watch(config: JobWatcherConfig, filter: &JobFilter, refresh: u64) -> i32 {
+    let (sender, receiver) = unbounded();
+    let _watcher = JobWatcherHandle::new(sender, config);
+    let mut stdout = io::stdout();
+    let mut jobs = Vec::new();
+    for message in receiver {
+        let screen = match message {
+            AppMessage::Jobs(new_jobs) => {
+                jobs = new_jobs;
+                list::text(&filter.apply(&jobs), &list::DEFAULT_FIELDS)
+            }
+            AppMessage::JobsChanged(changes) => {
+                apply_changes(&mut jobs, changes);
+                list::text(&filter.apply(&jobs), &list::DEFAULT_FIELDS)
+            }
+            AppMessage::SqueueError(e) => format!("{}\n", e),
+            AppMessage::WatcherDied(e) => {
+                eprintln!("{}", e);
+                return 1;
+            }
+            _ => continue,
+        };
+        // clear the screen and move the cursor home
+        let written = write!(
+            stdout,
+            "\x1b[2J\x1b[HEvery {}s: turm    {}\n\n{}",
+            refresh,
+            Local::now().format("%F %T"),
+            screen
+        )
+        .and_then(|()| stdout.flush());
+        if written.is_err() {
+            // e.g. the other end of a pipe closed
+            return 0;
+        }
+    }
+    1
+}
+
+/// The states of the job and its array tasks according to sacct.
+fn states_of(job_id: &str) -> Vec<String> {
+    sacct_states(job_id)
+        .into_iter()
+        .map(|(_, state)| state)
+        .collect()
+}
+
+/// `H:MM:SS`, like Slurm prints durations.
+fn format_elapsed(d: Duration) -> String {
+    let s = d.as_secs();
+    format!("{}:{:02}:{:02}", s / 3600, s / 60 % 60, s % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_executable() {
+        assert_eq!(parse_executable("/bin/sh"), Ok(PathBuf::from("/bin/sh")));
+        assert_eq!(parse_executable("sh"), Ok(PathBuf::from("sh")));
+        assert!(parse_executable(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml")).is_err());
+        assert!(parse_executable("/bin").is_err());
+        assert!(parse_executable("/nonexistent/squeue").is_err());
+        assert!(parse_executable("turm-nonexistent-binary").is_err());
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(Duration::from_secs(59)), "0:00:59");
+        assert_eq!(format_elapsed(Duration::from_secs(3723)), "1:02:03");
+        assert_eq!(format_elapsed(Duration::from_secs(100 * 3600)), "100:00:00");
+    }
+
+    #[test]
+    fn test_parse_sbatch_output() {
+        assert_eq!(parse_sbatch_output("1234\n"), ["1234"]);
+        assert_eq!(
+            parse_sbatch_output("1234;cluster1\n1235\n"),
+            ["1234", "1235"]
+        );
+        assert!(parse_sbatch_output("sbatch: warning: low priority\n").is_empty());
+    }
+}
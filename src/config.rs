@@ -0,0 +1,497 @@
+use std::{
+    env, fs,
+    io::{self, ErrorKind},
+    path::PathBuf,
+};
+
+use serde::Deserialize;
+use toml_edit::{table, value, Array, DocumentMut, Item};
+
+use crate::columns::ColumnSet;
+use crate::keybindings::Keybindings;
+use crate::sort::SortKey;
+use crate::theme::ThemeName;
+
+/// Written by `turm --init-config`.
+const DEFAULT_CONFIG: &str = r#"# turm configuration, command line flags take precedence.
+
+# Refresh rate for the job watcher in seconds.
+slurm-refresh = 2
+
+# Refresh rate for the file watcher in seconds.
+file-refresh = 2
+
+# Longest wait between squeue retries in seconds, and how many failures in a
+# row to tolerate.
+max-backoff = 60
+max-retries = 10
+
+# Also show finished jobs from sacct.
+sacct = false
+
+# Send a desktop notification when a job enters one of these states.
+notify-on = ["COMPLETED", "FAILED", "TIMEOUT"]
+
+# Only show jobs of this user, `me` is the current user.
+# filter-user = "me"
+
+# Visible columns of the job list, in display order. Available are state,
+# job-id, cluster, partition, user, account, time, time-limit, priority,
+# progress, gpu-warning and name.
+columns = ["state", "job-id", "partition", "user", "time", "progress", "gpu-warning", "name"]
+
+# Running jobs with GPUs get a `!` in the gpu-warning column after this many
+# minutes.
+gpu-warning-minutes = 5
+
+# Longer job names are cut off with `…`, +/- change the width in the TUI.
+name-max-width = 30
+
+# Sort the job list by job-id, name, state, user, elapsed, time-limit,
+# priority, partition, gpu or cpu. Without, jobs are shown in squeue order.
+# sort = "job-id"
+# sort-ascending = true
+
+# Colors of the TUI, `default` or `solarized-dark`.
+# theme = "default"
+
+# Keys for each action, a single character, a key name (enter, esc, tab,
+# backtab, backspace, delete, insert, space, up, down, left, right, home,
+# end, f1 to f12) or a list of them.
+[keybindings]
+quit = "q"
+up = ["k", "up"]
+down = ["j", "down"]
+next-page = "f"
+previous-page = "b"
+focus-previous = ["h", "left"]
+focus-next = ["l", "right"]
+toggle-log = "enter"
+cancel-job = "c"
+details = "d"
+hold-job = "H"
+release-job = "R"
+switch-output = ["o", "tab"]
+wrap = "w"
+sort = "s"
+columns = "C"
+filter = "/"
+move-column-up = "K"
+move-column-down = "J"
+toggle-column = "space"
+jobs-tab = "1"
+users-tab = "2"
+partitions-tab = "3"
+dependencies-tab = "4"
+accounts-tab = "5"
+ssh = "S"
+attach = "a"
+refresh = "r"
+copy-id = "y"
+export = "e"
+wider-name = "+"
+narrower-name = "-"
+dependency = "D"
+help = "?"
+"#;
+
+/// Other names of top level keys, like the `--interval` alias of the flag.
+const ALIASES: [(&str, &str); 1] = [("interval", "slurm-refresh")];
+
+/// The top level keys, for `turm config`. Keys are `keybindings.<action>`.
+const KEYS: [&str; 13] = [
+    "slurm-refresh",
+    "file-refresh",
+    "max-backoff",
+    "max-retries",
+    "sacct",
+    "notify-on",
+    "filter-user",
+    "columns",
+    "name-max-width",
+    "gpu-warning-minutes",
+    "sort",
+    "sort-ascending",
+    "theme",
+];
+
+/// Settings from the config file, the keys mirror the command line flags.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    #[serde(alias = "interval")]
+    pub slurm_refresh: Option<u64>,
+    pub file_refresh: Option<u64>,
+    pub max_backoff: Option<u64>,
+    pub max_retries: Option<u32>,
+    pub sacct: Option<bool>,
+    pub notify_on: Option<Vec<String>>,
+    pub filter_user: Option<String>,
+    pub columns: Option<ColumnSet>,
+    pub sort: Option<SortKey>,
+    pub sort_ascending: Option<bool>,
+    pub name_max_width: Option<usize>,
+    pub gpu_warning_minutes: Option<u64>,
+    pub theme: Option<ThemeName>,
+    pub keybindings: Keybindings,
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/turm/config.toml`, or `~/.config/turm/config.toml`.
+    pub fn path() -> Option<PathBuf> {
+        env::var_os("XDG_CONFIG_HOME")
+            .filter(|d| !d.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))
+            .map(|d| d.join("turm").join("config.toml"))
+    }
+
+    /// A missing config file is the same as an empty one.
+    pub fn load() -> io::Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        match fs::read_to_string(&path) {
+            Ok(s) => Self::parse(&s).map_err(|e| {
+                io::Error::new(ErrorKind::InvalidData, format!("{}: {}", path.display(), e))
+            }),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn parse(s: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(s).map_err(|e| e.to_string())?;
+        if config.slurm_refresh == Some(0) {
+            return Err("slurm-refresh must be at least 1".to_string());
+        }
+        if config.file_refresh == Some(0) {
+            return Err("file-refresh must be at least 1".to_string());
+        }
+        if config.name_max_width == Some(0) {
+            return Err("name-max-width must be at least 1".to_string());
+        }
+        if config.max_retries == Some(0) {
+            return Err("max-retries must be at least 1".to_string());
+        }
+        Ok(config)
+    }
+
+    /// Writes the default config file and returns its path. An existing file
+    /// is left untouched.
+    pub fn init() -> io::Result<PathBuf> {
+        let path = Self::path()
+            .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "no config directory"))?;
+        if path.exists() {
+            return Err(io::Error::new(
+                ErrorKind::AlreadyExists,
+                format!("{} already exists", path.display()),
+            ));
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, DEFAULT_CONFIG)?;
+        Ok(path)
+    }
+
+    fn read() -> io::Result<(PathBuf, String)> {
+        let path = Self::path()
+            .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "no config directory"))?;
+        match fs::read_to_string(&path) {
+            Ok(s) => Ok((path, s)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok((path, String::new())),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn write(path: &PathBuf, content: &str) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, content)
+    }
+
+    /// Every key with its value in the config file, or else its default.
+    /// `None` for keys without either.
+    pub fn list() -> io::Result<Vec<(String, Option<String>)>> {
+        let (_, content) = Self::read()?;
+        let doc = parse_document(&content)?;
+        Ok(keys()
+            .into_iter()
+            .map(|key| {
+                let value = effective_value(&doc, &key);
+                (key, value)
+            })
+            .collect())
+    }
+
+    pub fn get(key: &str) -> io::Result<Option<String>> {
+        let key = canonical_key(key);
+        if !keys().iter().any(|k| k == key) {
+            return Err(unknown_key(key));
+        }
+        let (_, content) = Self::read()?;
+        Ok(effective_value(&parse_document(&content)?, key))
+    }
+
+    /// Sets `key` in the config file, `value` is TOML or else a string. The
+    /// rest of the file is kept as is.
+    pub fn set(key: &str, value: &str) -> io::Result<()> {
+        let (path, content) = Self::read()?;
+        let content = set_value(&content, key, value)
+            .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+        Self::write(&path, &content)
+    }
+
+    pub fn sort_order(&self) -> Option<(SortKey, bool)> {
+        self.sort
+            .map(|key| (key, self.sort_ascending.unwrap_or(true)))
+    }
+
+    /// Stores the columns and sort order chosen in the TUI. The rest of the
+    /// file, including comments, is kept as is.
+    pub fn save_view(columns: &ColumnSet, sort: Option<(SortKey, bool)>) -> io::Result<()> {
+        let (path, content) = Self::read()?;
+        let content = update_view(&content, columns, sort)
+            .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+        Self::write(&path, &content)
+    }
+}
+
+fn keys() -> Vec<String> {
+    let defaults = parse_document(DEFAULT_CONFIG).unwrap_or_default();
+    let actions = defaults
+        .get("keybindings")
+        .and_then(Item::as_table)
+        .into_iter()
+        .flat_map(|t| t.iter().map(|(k, _)| format!("keybindings.{}", k)));
+    KEYS.iter().map(|k| k.to_string()).chain(actions).collect()
+}
+
+fn canonical_key(key: &str) -> &str {
+    ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == key)
+        .map_or(key, |(_, key)| key)
+}
+
+fn aliases(key: &str) -> impl Iterator<Item = &'static str> + '_ {
+    ALIASES
+        .iter()
+        .filter(move |(_, k)| *k == key)
+        .map(|(alias, _)| *alias)
+}
+
+fn unknown_key(key: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidInput, format!("unknown key `{}`", key))
+}
+
+fn parse_document(content: &str) -> io::Result<DocumentMut> {
+    content
+        .parse()
+        .map_err(|e| io::Error::new(ErrorKind::InvalidData, format!("{}", e)))
+}
+
+/// The value of a key like `sort` or `keybindings.quit`, without comments.
+fn lookup(doc: &DocumentMut, key: &str) -> Option<String> {
+    let item = match key.split_once('.') {
+        Some((table, key)) => doc.get(table)?.get(key)?,
+        None => doc
+            .get(key)
+            .or_else(|| aliases(key).find_map(|k| doc.get(k)))?,
+    };
+    let mut value = item.as_value()?.clone();
+    value.decor_mut().clear();
+    Some(value.to_string())
+}
+
+fn effective_value(doc: &DocumentMut, key: &str) -> Option<String> {
+    lookup(doc, key).or_else(|| lookup(&parse_document(DEFAULT_CONFIG).ok()?, key))
+}
+
+fn set_value(content: &str, key: &str, value: &str) -> Result<String, String> {
+    let key = canonical_key(key);
+    if !keys().iter().any(|k| k == key) {
+        return Err(unknown_key(key).to_string());
+    }
+    let mut doc: DocumentMut = content.parse().map_err(|e| format!("{}", e))?;
+    // a value under an alias would be a duplicate
+    for alias in aliases(key) {
+        if let Some((_, old)) = doc.remove_entry(alias) {
+            doc[key] = old;
+        }
+    }
+    let mut value = value
+        .parse::<toml_edit::Value>()
+        .unwrap_or_else(|_| value.into());
+    let item = match key.split_once('.') {
+        Some((name, key)) => &mut doc.entry(name).or_insert(table())[key],
+        None => &mut doc[key],
+    };
+    // keep comments after the old value
+    if let Some(old) = item.as_value() {
+        *value.decor_mut() = old.decor().clone();
+    }
+    *item = Item::Value(value);
+    let content = doc.to_string();
+    // the same checks as when loading the file
+    Config::parse(&content).map_err(|e| format!("invalid value for `{}`: {}", key, e))?;
+    Ok(content)
+}
+
+fn update_view(
+    content: &str,
+    columns: &ColumnSet,
+    sort: Option<(SortKey, bool)>,
+) -> Result<String, String> {
+    let mut doc: DocumentMut = content.parse().map_err(|e| format!("{}", e))?;
+
+    let names = |v: toml::Value| match v {
+        toml::Value::Array(a) => a
+            .into_iter()
+            .filter_map(|v| v.as_str().map(str::to_owned))
+            .collect(),
+        toml::Value::String(s) => vec![s],
+        _ => Vec::new(),
+    };
+    let columns = toml::Value::try_from(columns).map_err(|e| e.to_string())?;
+    doc["columns"] = value(names(columns).into_iter().collect::<Array>());
+
+    match sort {
+        Some((key, ascending)) => {
+            let key = toml::Value::try_from(key).map_err(|e| e.to_string())?;
+            doc["sort"] = value(names(key).concat());
+            doc["sort-ascending"] = value(ascending);
+        }
+        None => {
+            doc.remove("sort");
+            doc.remove("sort-ascending");
+        }
+    }
+
+    Ok(doc.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::columns::Column;
+    use crate::keybindings::Action;
+    use crossterm::event::KeyCode;
+
+    #[test]
+    fn test_default_config() {
+        let config = Config::parse(DEFAULT_CONFIG).unwrap();
+        assert_eq!(config.slurm_refresh, Some(2));
+        assert_eq!(config.filter_user, None);
+        assert!(config
+            .columns
+            .as_ref()
+            .unwrap()
+            .visible()
+            .eq(ColumnSet::default().visible()));
+        assert_eq!(config.sort_order(), None);
+        assert_eq!(config.keybindings, Keybindings::default());
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse(
+            r#"
+            filter-user = "me"
+            columns = ["job-id", "name"]
+            sort = "time-limit"
+            sort-ascending = false
+            theme = "solarized-dark"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.filter_user.as_deref(), Some("me"));
+        assert_eq!(
+            config
+                .columns
+                .as_ref()
+                .unwrap()
+                .visible()
+                .collect::<Vec<_>>(),
+            [Column::State, Column::JobId, Column::Name]
+        );
+        assert_eq!(config.sort_order(), Some((SortKey::TimeLimit, false)));
+        assert_eq!(config.theme, Some(ThemeName::SolarizedDark));
+
+        let config = Config::parse("[keybindings]\nquit = \"x\"").unwrap();
+        assert_eq!(
+            config.keybindings.action(KeyCode::Char('x')),
+            Some(Action::Quit)
+        );
+        assert_eq!(config.keybindings.action(KeyCode::Char('q')), None);
+        assert_eq!(config.keybindings.details, Keybindings::default().details);
+        assert!(Config::parse("[keybindings]\nquit = \"ctrl-q\"").is_err());
+
+        assert_eq!(
+            Config::parse("interval = 2").unwrap().slurm_refresh,
+            Some(2)
+        );
+        assert!(Config::parse("slurm-refresh = 0").is_err());
+        assert!(Config::parse("file-refresh = 0").is_err());
+        assert!(Config::parse("name-max-width = 0").is_err());
+        assert!(Config::parse(r#"sort = "size""#).is_err());
+        assert!(Config::parse(r#"theme = "light""#).is_err());
+    }
+
+    #[test]
+    fn test_set_value() {
+        let content = "# my settings\nslurm-refresh = 5 # fast\n";
+        let updated = set_value(content, "slurm-refresh", "10").unwrap();
+        assert_eq!(updated, "# my settings\nslurm-refresh = 10 # fast\n");
+
+        let updated = set_value(&updated, "filter-user", "me").unwrap();
+        let updated = set_value(&updated, "keybindings.quit", "x").unwrap();
+        let doc = parse_document(&updated).unwrap();
+        assert_eq!(lookup(&doc, "filter-user").as_deref(), Some("\"me\""));
+        assert_eq!(lookup(&doc, "keybindings.quit").as_deref(), Some("\"x\""));
+        assert_eq!(effective_value(&doc, "max-retries").as_deref(), Some("10"));
+        assert_eq!(effective_value(&doc, "sort"), None);
+
+        let updated = set_value(content, "interval", "10").unwrap();
+        assert_eq!(updated, "# my settings\nslurm-refresh = 10 # fast\n");
+        let updated = set_value("interval = 5 # fast\n", "slurm-refresh", "10").unwrap();
+        assert_eq!(updated, "slurm-refresh = 10 # fast\n");
+        let doc = parse_document("interval = 5\n").unwrap();
+        assert_eq!(lookup(&doc, "slurm-refresh").as_deref(), Some("5"));
+        assert!(set_value(content, "refresh", "10").is_err());
+        assert!(set_value(content, "slurm-refresh", "0").is_err());
+        assert!(set_value(content, "columns", "[\"size\"]").is_err());
+    }
+
+    #[test]
+    fn test_keys() {
+        // every key can be set to its default
+        let doc = parse_document(DEFAULT_CONFIG).unwrap();
+        for key in keys() {
+            if let Some(value) = lookup(&doc, &key) {
+                set_value("", &key, &value).unwrap();
+            }
+        }
+        assert!(keys().contains(&"theme".to_owned()));
+        assert!(keys().contains(&"keybindings.copy-id".to_owned()));
+    }
+
+    #[test]
+    fn test_update_view() {
+        let content = "# my settings\nslurm-refresh = 5 # fast\n\n[keybindings]\nquit = \"x\"\n";
+        let columns = ColumnSet::from(vec![Column::State, Column::JobId]);
+
+        let updated = update_view(content, &columns, Some((SortKey::JobId, true))).unwrap();
+        assert!(updated.starts_with("# my settings\nslurm-refresh = 5 # fast\n"));
+        let config = Config::parse(&updated).unwrap();
+        assert_eq!(config.slurm_refresh, Some(5));
+        assert_eq!(config.columns, Some(columns.clone()));
+        assert_eq!(config.sort_order(), Some((SortKey::JobId, true)));
+        assert_eq!(config.keybindings.quit.label(), "x");
+
+        let updated = update_view(&updated, &columns, None).unwrap();
+        assert_eq!(Config::parse(&updated).unwrap().sort_order(), None);
+    }
+}
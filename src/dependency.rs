@@ -0,0 +1,243 @@
+use std::collections::{HashMap, HashSet};
+
+use petgraph::{
+    graph::{DiGraph, NodeIndex},
+    visit::EdgeRef,
+    Direction,
+};
+
+use crate::job::Job;
+
+/// One condition of a job's `--dependency`, e.g. `afterok:1234:1235`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dependency {
+    /// `after`, `afterany`, `afterok`, `singleton` etc.
+    pub kind: String,
+    pub job_ids: Vec<String>,
+}
+
+/// Parses the dependency field of squeue, e.g.
+/// `afterok:1234(unfulfilled),afterany:1235_*+10(unfulfilled)`. Conditions
+/// are separated by `,` if all must hold, by `?` if any suffices.
+pub fn parse_dependencies(s: &str) -> Vec<Dependency> {
+    s.split([',', '?'])
+        .filter_map(|condition| {
+            // squeue appends the state of the condition
+            let condition = condition.split('(').next().unwrap_or_default();
+            let mut parts = condition.split(':');
+            let kind = parts.next().filter(|k| !k.is_empty())?;
+            let job_ids = parts
+                // `after:1234+10` waits 10 minutes after the start
+                .map(|id| id.split('+').next().unwrap_or_default())
+                // the whole array
+                .map(|id| id.strip_suffix("_*").unwrap_or(id))
+                .filter(|id| !id.is_empty())
+                .map(str::to_owned)
+                .collect();
+            Some(Dependency {
+                kind: kind.to_owned(),
+                job_ids,
+            })
+        })
+        .collect()
+}
+
+/// The dependencies between `jobs`, from the job that must finish first to
+/// the one waiting for it, with the kind of dependency as weight. Nodes are
+/// indices into `jobs`, jobs without dependencies among `jobs` are left out.
+pub fn dependency_graph(jobs: &[Job]) -> DiGraph<usize, String> {
+    // array jobs can be referred to as a whole
+    let mut by_id: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, job) in jobs.iter().enumerate() {
+        by_id.entry(job.id()).or_default().push(i);
+        if job.array_step.is_some() {
+            by_id.entry(job.array_id.clone()).or_default().push(i);
+        }
+    }
+
+    let mut graph = DiGraph::new();
+    let mut nodes = HashMap::new();
+    let mut node = |graph: &mut DiGraph<usize, String>, i: usize| {
+        *nodes.entry(i).or_insert_with(|| graph.add_node(i))
+    };
+    for (i, job) in jobs.iter().enumerate() {
+        for dependency in job.dependencies() {
+            for id in &dependency.job_ids {
+                for &before in by_id.get(id).into_iter().flatten() {
+                    let from = node(&mut graph, before);
+                    let to = node(&mut graph, i);
+                    graph.update_edge(from, to, dependency.kind.clone());
+                }
+            }
+        }
+    }
+    graph
+}
+
+/// A line of the dependency tree as drawn by [`graph_lines`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct GraphLine {
+    /// Tree drawing characters, e.g. `│  └─ `.
+    pub prefix: String,
+    /// The dependency on the job of the parent line.
+    pub kind: Option<String>,
+    /// Index into the jobs of the graph.
+    pub job: usize,
+    /// The job was already drawn with its dependents further up.
+    pub repeated: bool,
+}
+
+/// Draws the graph as a tree like `cargo tree`: jobs that wait for nobody
+/// first, the jobs waiting for them below. A job that waits for several
+/// jobs appears below each of them, but its dependents only once.
+pub fn graph_lines(graph: &DiGraph<usize, String>) -> Vec<GraphLine> {
+    let mut roots: Vec<NodeIndex> = graph
+        .node_indices()
+        .filter(|&n| {
+            graph
+                .neighbors_directed(n, Direction::Incoming)
+                .next()
+                .is_none()
+        })
+        .collect();
+    roots.sort_by_key(|&n| graph[n]);
+
+    let mut lines = Vec::new();
+    let mut drawn = HashSet::new();
+    for root in roots {
+        draw(graph, root, None, "", "", &mut drawn, &mut lines);
+    }
+    // every node of a cycle has a dependency, Slurm rejects them anyway
+    let mut rest: Vec<NodeIndex> = graph
+        .node_indices()
+        .filter(|n| !drawn.contains(n))
+        .collect();
+    rest.sort_by_key(|&n| graph[n]);
+    for node in rest {
+        if !drawn.contains(&node) {
+            draw(graph, node, None, "", "", &mut drawn, &mut lines);
+        }
+    }
+    lines
+}
+
+fn draw(
+    graph: &DiGraph<usize, String>,
+    node: NodeIndex,
+    kind: Option<&str>,
+    prefix: &str,
+    child_prefix: &str,
+    drawn: &mut HashSet<NodeIndex>,
+    lines: &mut Vec<GraphLine>,
+) {
+    let repeated = !drawn.insert(node);
+    lines.push(GraphLine {
+        prefix: prefix.to_owned(),
+        kind: kind.map(str::to_owned),
+        job: graph[node],
+        repeated,
+    });
+    if repeated {
+        return;
+    }
+    let mut edges: Vec<_> = graph.edges_directed(node, Direction::Outgoing).collect();
+    edges.sort_by_key(|e| graph[e.target()]);
+    for (i, edge) in edges.iter().enumerate() {
+        let last = i + 1 == edges.len();
+        draw(
+            graph,
+            edge.target(),
+            Some(edge.weight()),
+            &format!("{}{}", child_prefix, if last { "└─ " } else { "├─ " }),
+            &format!("{}{}", child_prefix, if last { "   " } else { "│  " }),
+            drawn,
+            lines,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(id: &str, dependency: Option<&str>) -> Job {
+        let (array_id, array_step) = match id.split_once('_') {
+            Some((array_id, step)) => (array_id, Some(step.to_owned())),
+            None => (id, None),
+        };
+        Job {
+            job_id: id.to_owned(),
+            array_id: array_id.to_owned(),
+            array_step,
+            state: "PENDING".to_owned(),
+            state_compact: "PD".to_owned(),
+            dependency: dependency.map(str::to_owned),
+            ..Job::test_default()
+        }
+    }
+
+    #[test]
+    fn test_graph_lines() {
+        let jobs = [
+            job("1", None),
+            job("2", Some("afterok:1(unfulfilled)")),
+            job("3_1", Some("afterany:1")),
+            job("3_2", Some("afterany:1")),
+            job("4", Some("afterok:2:3")),
+            // not part of the graph
+            job("5", None),
+            job("6", Some("afterok:999")),
+        ];
+        let graph = dependency_graph(&jobs);
+        assert_eq!(graph.node_count(), 5);
+
+        let lines: Vec<_> = graph_lines(&graph)
+            .into_iter()
+            .map(|l| {
+                format!(
+                    "{}{}{}{}",
+                    l.prefix,
+                    l.kind.map(|k| k + " ").unwrap_or_default(),
+                    jobs[l.job].id(),
+                    if l.repeated { " *" } else { "" }
+                )
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "1",
+                "├─ afterok 2",
+                "│  └─ afterok 4",
+                "├─ afterany 3_1",
+                "│  └─ afterok 4 *",
+                "└─ afterany 3_2",
+                "   └─ afterok 4 *",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_dependencies() {
+        let dependency = |kind: &str, job_ids: &[&str]| Dependency {
+            kind: kind.to_owned(),
+            job_ids: job_ids.iter().map(|&id| id.to_owned()).collect(),
+        };
+        assert_eq!(
+            parse_dependencies("afterok:1234(unfulfilled)"),
+            [dependency("afterok", &["1234"])]
+        );
+        assert_eq!(
+            parse_dependencies("afterok:1234:1235_7,afterany:1236_*(unfulfilled)"),
+            [
+                dependency("afterok", &["1234", "1235_7"]),
+                dependency("afterany", &["1236"])
+            ]
+        );
+        assert_eq!(
+            parse_dependencies("after:1234+10?singleton"),
+            [dependency("after", &["1234"]), dependency("singleton", &[])]
+        );
+        assert!(parse_dependencies("").is_empty());
+    }
+}
@@ -0,0 +1,314 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    hash::Hash,
+    path::Path,
+};
+
+use serde::Deserialize;
+
+use crate::job::Job;
+
+/// The fields of a job in `--output json` that `turm diff` compares, other
+/// fields are ignored so snapshots of older versions still work.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+pub struct SnapshotJob {
+    pub job_id: String,
+    pub array_id: String,
+    pub array_step: Option<String>,
+    pub name: String,
+    pub user: String,
+    pub state: String,
+}
+
+impl SnapshotJob {
+    /// Like [`crate::job::Job::id`].
+    pub fn id(&self) -> String {
+        match &self.array_step {
+            Some(step) => format!("{}_{}", self.array_id, step),
+            None => self.job_id.clone(),
+        }
+    }
+}
+
+pub fn read_snapshot(path: &Path) -> Result<Vec<SnapshotJob>, String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    serde_json::from_str(&json).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Change<'a> {
+    New(&'a SnapshotJob),
+    /// Not in the queue anymore, it finished or was cancelled.
+    Finished(&'a SnapshotJob),
+    StateChanged {
+        job: &'a SnapshotJob,
+        before: &'a str,
+    },
+}
+
+/// The jobs only in `new`, those only in `old` and the pairs of old and new
+/// job with a different and with the same state, each in the order of its
+/// list. Jobs are matched by `id`.
+struct Changes<'a, T> {
+    added: Vec<&'a T>,
+    removed: Vec<&'a T>,
+    state_changed: Vec<(&'a T, &'a T)>,
+    same_state: Vec<(&'a T, &'a T)>,
+}
+
+fn changes<'a, T, K: Eq + Hash>(
+    old: &'a [T],
+    new: &'a [T],
+    id: impl Fn(&'a T) -> K,
+    state: impl Fn(&T) -> &str,
+) -> Changes<'a, T> {
+    let old_by_id: HashMap<K, &T> = old.iter().map(|j| (id(j), j)).collect();
+    let new_by_id: HashMap<K, &T> = new.iter().map(|j| (id(j), j)).collect();
+    let (state_changed, same_state) = new
+        .iter()
+        .filter_map(|j| Some((*old_by_id.get(&id(j))?, j)))
+        .partition(|(old, new)| state(old) != state(new));
+    Changes {
+        added: new
+            .iter()
+            .filter(|j| !old_by_id.contains_key(&id(j)))
+            .collect(),
+        removed: old
+            .iter()
+            .filter(|j| !new_by_id.contains_key(&id(j)))
+            .collect(),
+        state_changed,
+        same_state,
+    }
+}
+
+/// New jobs, finished jobs and jobs with a changed state, in that order and
+/// each in the order of its snapshot.
+pub fn diff<'a>(before: &'a [SnapshotJob], after: &'a [SnapshotJob]) -> Vec<Change<'a>> {
+    let changes = changes(before, after, SnapshotJob::id, |j| &j.state);
+    let new = changes.added.into_iter().map(Change::New);
+    let finished = changes.removed.into_iter().map(Change::Finished);
+    let changed = changes
+        .state_changed
+        .into_iter()
+        .map(|(before, job)| Change::StateChanged {
+            job,
+            before: &before.state,
+        });
+    new.chain(finished).chain(changed).collect()
+}
+
+/// What changed between two polls of squeue.
+#[derive(Debug, PartialEq, Eq)]
+pub enum JobChange {
+    Added(Job),
+    /// Not in the queue anymore, it finished or was cancelled.
+    Removed(Job),
+    StateChanged {
+        job: Job,
+        old_state: String,
+    },
+    /// In the same state, but e.g. its elapsed time changed.
+    Updated(Job),
+}
+
+/// Like [`diff`], for jobs matched by `job_id`, which is unique for array
+/// tasks as well. Jobs that changed in another way than their state come
+/// last.
+pub fn jobs_changed(old: &[Job], new: &[Job]) -> Vec<JobChange> {
+    let changes = changes(old, new, |j| j.job_id.as_str(), |j| &j.state);
+    let added = changes
+        .added
+        .into_iter()
+        .map(|j| JobChange::Added(j.clone()));
+    let removed = changes
+        .removed
+        .into_iter()
+        .map(|j| JobChange::Removed(j.clone()));
+    let changed = changes
+        .state_changed
+        .into_iter()
+        .map(|(old, job)| JobChange::StateChanged {
+            job: job.clone(),
+            old_state: old.state.clone(),
+        });
+    let updated = changes
+        .same_state
+        .into_iter()
+        .filter(|(old, job)| old != job)
+        .map(|(_, job)| JobChange::Updated(job.clone()));
+    added.chain(removed).chain(changed).chain(updated).collect()
+}
+
+/// Turns the `jobs` that [`jobs_changed`] was given as `old` into what it
+/// was given as `new`, except that added jobs end up last.
+pub fn apply_changes(jobs: &mut Vec<Job>, changes: Vec<JobChange>) {
+    let index: HashMap<String, usize> = jobs
+        .iter()
+        .enumerate()
+        .map(|(i, j)| (j.job_id.clone(), i))
+        .collect();
+    let mut removed: HashSet<usize> = HashSet::new();
+    let mut added = Vec::new();
+    for change in changes {
+        match change {
+            JobChange::Added(job) => added.push(job),
+            JobChange::Removed(job) => removed.extend(index.get(&job.job_id).copied()),
+            JobChange::StateChanged { job, .. } | JobChange::Updated(job) => {
+                if let Some(&i) = index.get(&job.job_id) {
+                    jobs[i] = job;
+                }
+            }
+        }
+    }
+    let mut i = 0;
+    jobs.retain(|_| {
+        i += 1;
+        !removed.contains(&(i - 1))
+    });
+    jobs.extend(added);
+}
+
+/// One line per change, new jobs in green, finished ones in red and changed
+/// ones in yellow if `color`.
+pub fn format(changes: &[Change], color: bool) -> String {
+    const GREEN: &str = "\x1b[32m";
+    const RED: &str = "\x1b[31m";
+    const YELLOW: &str = "\x1b[33m";
+    const RESET: &str = "\x1b[0m";
+
+    changes
+        .iter()
+        .map(|change| {
+            let (code, line) = match change {
+                Change::New(j) => (
+                    GREEN,
+                    format!("+ {} {} {} {}", j.id(), j.name, j.user, j.state),
+                ),
+                Change::Finished(j) => (
+                    RED,
+                    format!("- {} {} {} {}", j.id(), j.name, j.user, j.state),
+                ),
+                Change::StateChanged { job: j, before } => (
+                    YELLOW,
+                    format!(
+                        "~ {} {} {} {} -> {}",
+                        j.id(),
+                        j.name,
+                        j.user,
+                        before,
+                        j.state
+                    ),
+                ),
+            };
+            if color {
+                format!("{}{}{}\n", code, line, RESET)
+            } else {
+                format!("{}\n", line)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job::{output_separator, test_squeue_line};
+
+    fn job(id: &str, state: &str) -> SnapshotJob {
+        SnapshotJob {
+            job_id: id.to_owned(),
+            array_id: id.to_owned(),
+            array_step: None,
+            name: "train".to_owned(),
+            user: "alice".to_owned(),
+            state: state.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let before = [
+            job("1", "RUNNING"),
+            job("2", "PENDING"),
+            job("3", "PENDING"),
+        ];
+        let after = [
+            job("2", "RUNNING"),
+            job("3", "PENDING"),
+            job("4", "PENDING"),
+        ];
+        let changes = diff(&before, &after);
+        assert_eq!(
+            changes,
+            [
+                Change::New(&after[2]),
+                Change::Finished(&before[0]),
+                Change::StateChanged {
+                    job: &after[0],
+                    before: "PENDING"
+                },
+            ]
+        );
+        assert_eq!(
+            format(&changes, false),
+            "+ 4 train alice PENDING\n\
+             - 1 train alice RUNNING\n\
+             ~ 2 train alice PENDING -> RUNNING\n"
+        );
+        assert!(diff(&after, &after).is_empty());
+    }
+
+    #[test]
+    fn test_jobs_changed() {
+        let separator = output_separator();
+        let job = |id: &str, state: &str, time: &str| {
+            let line = test_squeue_line(
+                &separator,
+                &[("jobid", id), ("state", state), ("timeused", time)],
+            );
+            Job::from_parts(&line, &separator).unwrap()
+        };
+
+        let old = [
+            job("1", "RUNNING", "1:00"),
+            job("2", "PENDING", "0:00"),
+            job("3", "RUNNING", "1:00"),
+            job("5", "RUNNING", "1:00"),
+        ];
+        let new = [
+            job("4", "PENDING", "0:00"),
+            job("1", "COMPLETING", "1:02"),
+            job("2", "PENDING", "0:00"),
+            job("5", "RUNNING", "1:02"),
+        ];
+        let changes = jobs_changed(&old, &new);
+        assert_eq!(
+            changes,
+            [
+                JobChange::Added(new[0].clone()),
+                JobChange::Removed(old[2].clone()),
+                JobChange::StateChanged {
+                    job: new[1].clone(),
+                    old_state: "RUNNING".to_owned()
+                },
+                JobChange::Updated(new[3].clone()),
+            ]
+        );
+        assert!(jobs_changed(&new, &new).is_empty());
+
+        let mut jobs = old.to_vec();
+        apply_changes(&mut jobs, changes);
+        assert_eq!(jobs, [&new[1..], &new[..1]].concat());
+    }
+
+    #[test]
+    fn test_snapshot_job() {
+        let json = r#"[{"job_id": "1236", "array_id": "1234", "array_step": "2",
+                        "name": "sweep", "user": "bob", "state": "RUNNING",
+                        "partition": "gpu"}]"#;
+        let jobs: Vec<SnapshotJob> = serde_json::from_str(json).unwrap();
+        assert_eq!(jobs[0].id(), "1234_2");
+    }
+}
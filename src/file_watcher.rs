@@ -13,7 +13,7 @@ use crossbeam::{
 };
 use notify::{event::ModifyKind, RecursiveMode, Watcher};
 
-use crate::app::AppMessage;
+use crate::app::{AppMessage, OutputFileView};
 
 struct FileReader {
     content_sender: Sender<io::Result<String>>,
@@ -21,11 +21,14 @@ struct FileReader {
     file_path: PathBuf,
     interval: Duration,
     content: String,
+    /// An incomplete UTF-8 sequence at the end of the last read.
+    pending: Vec<u8>,
     pos: u64,
 }
 
 struct FileWatcher {
     app: Sender<AppMessage>,
+    view: OutputFileView,
     receiver: Receiver<FileWatcherMessage>,
     file_path: Option<PathBuf>,
     interval: Duration,
@@ -56,14 +59,16 @@ impl fmt::Display for FileWatcherError {
 impl FileWatcher {
     fn new(
         app: Sender<AppMessage>,
+        view: OutputFileView,
         receiver: Receiver<FileWatcherMessage>,
         interval: Duration,
     ) -> Self {
         FileWatcher {
-            app: app,
-            receiver: receiver,
+            app,
+            view,
+            receiver,
             file_path: None,
-            interval: interval,
+            interval,
         }
     }
 
@@ -71,11 +76,8 @@ impl FileWatcher {
         let (watch_sender, watch_receiver) = unbounded();
         let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
             let event = res.unwrap();
-            match event.kind {
-                notify::EventKind::Modify(ModifyKind::Data(_)) => {
-                    watch_sender.send(event.paths).unwrap();
-                }
-                _ => {}
+            if let notify::EventKind::Modify(ModifyKind::Data(_)) = event.kind {
+                watch_sender.send(event.paths).unwrap();
             };
         })
         .unwrap();
@@ -91,7 +93,7 @@ impl FileWatcher {
                             (_watch_sender, _watch_receiver) = unbounded::<()>();
 
                             if let Some(p) = &self.file_path {
-                                watcher.unwatch(p).expect(format!("Failed to unwatch {:?}", p).as_str());
+                                watcher.unwatch(p).unwrap_or_else(|_| panic!("Failed to unwatch {:?}", p));
                                 self.file_path = None;
                             }
 
@@ -100,10 +102,10 @@ impl FileWatcher {
                                 match res {
                                     Ok(_) => {
                                         self.file_path = Some(p.clone());
-                                        let i = self.interval.clone();
+                                        let i = self.interval;
                                         thread::spawn(move || FileReader::new(_content_sender, _watch_receiver, p, i).run());
                                     },
-                                    Err(e) => self.app.send(AppMessage::JobOutput(Err(FileWatcherError::Watcher(e)))).unwrap()
+                                    Err(e) => self.app.send(AppMessage::JobOutput(self.view, Err(FileWatcherError::Watcher(e)))).unwrap()
                                 };
                             } else {
                                 _content_sender.send(Ok("".to_string())).unwrap();
@@ -113,7 +115,7 @@ impl FileWatcher {
                 }
                 recv(watch_receiver) -> _ => { _watch_sender.send(()).unwrap(); }
                 recv(_content_receiver) -> msg => {
-                    self.app.send(AppMessage::JobOutput(msg.unwrap().map_err(|e| FileWatcherError::File(e)))).unwrap();
+                    self.app.send(AppMessage::JobOutput(self.view, msg.unwrap().map_err(FileWatcherError::File))).unwrap();
                 }
             }
         }
@@ -128,11 +130,12 @@ impl FileReader {
         interval: Duration,
     ) -> Self {
         FileReader {
-            content_sender: content_sender,
-            receiver: receiver,
-            file_path: file_path,
-            interval: interval,
+            content_sender,
+            receiver,
+            file_path,
+            interval,
             content: "".to_string(),
+            pending: Vec::new(),
             pos: 0,
         }
     }
@@ -154,7 +157,9 @@ impl FileReader {
         let s = File::open(&self.file_path).and_then(|mut f| {
             // avoid reading the whole file every time
             self.pos = f.seek(io::SeekFrom::Start(self.pos))?;
-            self.pos += f.read_to_string(&mut self.content)? as u64;
+            let mut bytes = std::mem::take(&mut self.pending);
+            self.pos += f.read_to_end(&mut bytes)? as u64;
+            self.pending = append_utf8(&mut self.content, &bytes);
             Ok(self.content.clone())
         });
         // let s = fs::read_to_string(&self.file_path); // alternative: always read the whole file
@@ -162,10 +167,35 @@ impl FileReader {
     }
 }
 
+/// Appends `bytes` to `s`, replacing invalid UTF-8 with U+FFFD. A multi-byte
+/// character cut off at the end is returned instead, so that it can be
+/// completed by the next read.
+fn append_utf8(s: &mut String, mut bytes: &[u8]) -> Vec<u8> {
+    loop {
+        match std::str::from_utf8(bytes) {
+            Ok(valid) => {
+                s.push_str(valid);
+                return Vec::new();
+            }
+            Err(e) => {
+                let (valid, rest) = bytes.split_at(e.valid_up_to());
+                s.push_str(std::str::from_utf8(valid).unwrap());
+                match e.error_len() {
+                    Some(len) => {
+                        s.push(char::REPLACEMENT_CHARACTER);
+                        bytes = &rest[len..];
+                    }
+                    None => return rest.to_vec(),
+                }
+            }
+        }
+    }
+}
+
 impl FileWatcherHandle {
-    pub fn new(app: Sender<AppMessage>, interval: Duration) -> Self {
+    pub fn new(app: Sender<AppMessage>, view: OutputFileView, interval: Duration) -> Self {
         let (sender, receiver) = unbounded();
-        let mut actor = FileWatcher::new(app, receiver, interval);
+        let mut actor = FileWatcher::new(app, view, receiver, interval);
         thread::spawn(move || actor.run());
 
         Self {
@@ -183,3 +213,28 @@ impl FileWatcherHandle {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_utf8() {
+        let mut s = String::new();
+        let bytes = "épochs: 10 ✓\n".as_bytes();
+
+        // `✓` is split between two reads
+        let split = bytes.len() - 2;
+        let pending = append_utf8(&mut s, &bytes[..split]);
+        assert_eq!(s, "épochs: 10 ");
+        assert_eq!(pending.len(), 2);
+
+        let rest = [pending.as_slice(), &bytes[split..]].concat();
+        assert!(append_utf8(&mut s, &rest).is_empty());
+        assert_eq!(s, "épochs: 10 ✓\n");
+
+        let mut s = String::new();
+        assert!(append_utf8(&mut s, b"a\xffb").is_empty());
+        assert_eq!(s, "a\u{fffd}b");
+    }
+}
@@ -0,0 +1,225 @@
+use std::fmt;
+
+use regex::Regex;
+
+use crate::job::Job;
+
+/// Client side filter for the job list. Unset fields match every job.
+#[derive(Clone, Debug, Default)]
+pub struct JobFilter {
+    pub user: Option<String>,
+    /// Matches either the full (`RUNNING`) or compact (`R`) state, ignoring case.
+    pub state: Option<String>,
+    /// Comma separated list of partitions.
+    pub partition: Option<String>,
+    /// Comma separated list of QOS names.
+    pub qos: Option<String>,
+    /// Comma separated list of accounts.
+    pub account: Option<String>,
+    pub reservation: Option<String>,
+    pub name_regex: Option<Regex>,
+}
+
+impl JobFilter {
+    pub fn is_empty(&self) -> bool {
+        self.user.is_none()
+            && self.state.is_none()
+            && self.partition.is_none()
+            && self.qos.is_none()
+            && self.account.is_none()
+            && self.reservation.is_none()
+            && self.name_regex.is_none()
+    }
+
+    pub fn matches(&self, job: &Job) -> bool {
+        self.user.as_ref().is_none_or(|u| job.user == *u)
+            && self.state.as_ref().is_none_or(|s| {
+                job.state.eq_ignore_ascii_case(s) || job.state_compact.eq_ignore_ascii_case(s)
+            })
+            && self
+                .partition
+                .as_ref()
+                .is_none_or(|p| p.split(',').any(|p| job.partition == p))
+            && self
+                .qos
+                .as_ref()
+                .is_none_or(|q| q.split(',').any(|q| job.qos == q))
+            && self
+                .account
+                .as_ref()
+                .is_none_or(|a| a.split(',').any(|a| job.account == a))
+            && self
+                .reservation
+                .as_ref()
+                .is_none_or(|r| job.reservation.as_ref() == Some(r))
+            && self
+                .name_regex
+                .as_ref()
+                .is_none_or(|r| r.is_match(&job.name))
+    }
+
+    pub fn apply<'a>(&self, jobs: &'a [Job]) -> Vec<&'a Job> {
+        jobs.iter().filter(|j| self.matches(j)).collect()
+    }
+}
+
+/// E.g. `user=alice state=R name=/train/`, empty without a filter.
+impl fmt::Display for JobFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields = [
+            ("user", self.user.clone()),
+            ("state", self.state.clone()),
+            ("partition", self.partition.clone()),
+            ("qos", self.qos.clone()),
+            ("account", self.account.clone()),
+            ("reservation", self.reservation.clone()),
+            ("name", self.name_regex.as_ref().map(|r| format!("/{}/", r))),
+        ];
+        let mut first = true;
+        for (name, value) in fields {
+            if let Some(value) = value {
+                if !first {
+                    write!(f, " ")?;
+                }
+                write!(f, "{}={}", name, value)?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(id: &str, name: &str, user: &str, state: &str, partition: &str) -> Job {
+        Job {
+            job_id: id.to_owned(),
+            array_id: id.to_owned(),
+            name: name.to_owned(),
+            state: state.to_owned(),
+            state_compact: state[..1].to_owned(),
+            user: user.to_owned(),
+            partition: partition.to_owned(),
+            ..Job::test_default()
+        }
+    }
+
+    fn ids(jobs: Vec<&Job>) -> Vec<String> {
+        jobs.iter().map(|j| j.id()).collect()
+    }
+
+    fn jobs() -> Vec<Job> {
+        vec![
+            job("1", "train-a", "alice", "RUNNING", "gpu"),
+            job("2", "eval", "bob", "PENDING", "cpu"),
+            job("3", "train-b", "bob", "RUNNING", "debug"),
+        ]
+    }
+
+    #[test]
+    fn test_empty_filter() {
+        let filter = JobFilter::default();
+        assert!(filter.is_empty());
+        assert_eq!(ids(filter.apply(&jobs())), ["1", "2", "3"]);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(JobFilter::default().to_string(), "");
+        let filter = JobFilter {
+            user: Some("bob".to_owned()),
+            partition: Some("gpu,debug".to_owned()),
+            name_regex: Some(Regex::new("^train").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(
+            filter.to_string(),
+            "user=bob partition=gpu,debug name=/^train/"
+        );
+    }
+
+    #[test]
+    fn test_filter_user() {
+        let filter = JobFilter {
+            user: Some("bob".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(ids(filter.apply(&jobs())), ["2", "3"]);
+    }
+
+    #[test]
+    fn test_filter_state() {
+        let filter = JobFilter {
+            state: Some("running".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(ids(filter.apply(&jobs())), ["1", "3"]);
+
+        let filter = JobFilter {
+            state: Some("P".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(ids(filter.apply(&jobs())), ["2"]);
+    }
+
+    #[test]
+    fn test_filter_partition() {
+        let filter = JobFilter {
+            partition: Some("gpu,debug".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(ids(filter.apply(&jobs())), ["1", "3"]);
+    }
+
+    #[test]
+    fn test_filter_qos() {
+        let mut jobs = jobs();
+        jobs[2].qos = "debug".to_owned();
+        let filter = JobFilter {
+            qos: Some("debug".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(ids(filter.apply(&jobs)), ["3"]);
+    }
+
+    #[test]
+    fn test_filter_account() {
+        let mut jobs = jobs();
+        jobs[1].account = "proj2".to_owned();
+        jobs[2].account = "proj3".to_owned();
+        let filter = JobFilter {
+            account: Some("proj2,proj3".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(ids(filter.apply(&jobs)), ["2", "3"]);
+    }
+
+    #[test]
+    fn test_filter_reservation() {
+        let mut jobs = jobs();
+        jobs[0].reservation = Some("maint".to_owned());
+        let filter = JobFilter {
+            reservation: Some("maint".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(ids(filter.apply(&jobs)), ["1"]);
+    }
+
+    #[test]
+    fn test_filter_name_regex() {
+        let filter = JobFilter {
+            name_regex: Some(Regex::new("^train-").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(ids(filter.apply(&jobs())), ["1", "3"]);
+
+        let filter = JobFilter {
+            user: Some("bob".to_owned()),
+            name_regex: Some(Regex::new("^train-").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(ids(filter.apply(&jobs())), ["3"]);
+    }
+}
@@ -0,0 +1,1098 @@
+use std::{
+    collections::hash_map::RandomState,
+    fmt,
+    hash::{BuildHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    process,
+    sync::OnceLock,
+};
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeDelta, TimeZone, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::dependency::{parse_dependencies, Dependency};
+use crate::nodelist;
+use crate::reason::JobReason;
+use crate::tres::TresResources;
+
+/// The `squeue --Format` fields requested by turm, in the order expected by
+/// [`Job::from_parts`].
+pub const SQUEUE_FIELDS: [&str; 28] = [
+    "jobid",
+    "name",
+    "state",
+    "username",
+    "timeused",
+    "tres-alloc",
+    "partition",
+    "nodelist",
+    "stdout",
+    "stderr",
+    "command",
+    "statecompact",
+    "reason",
+    "ArrayJobID",    // %A
+    "ArrayTaskID",   // %a
+    "NodeList",      // %N
+    "WorkDir",       // for fallback
+    "tres-per-node", // appended to tres
+    "timelimit",
+    "submittime",
+    "starttime",
+    "PriorityLong",
+    "qos",
+    "reservation",
+    "cluster",
+    "dependency",
+    "account",
+    "BatchHost",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobSource {
+    Squeue,
+    Sacct,
+}
+
+/// Equal jobs have the same values in every field. Hashed by `job_id` only,
+/// so that the same job from two polls lands in the same bucket.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Job {
+    pub job_id: String,
+    pub array_id: String,
+    pub array_step: Option<String>,
+    pub name: String,
+    pub state: String,
+    pub state_compact: String,
+    pub reason: Option<JobReason>,
+    /// Pending jobs with a higher priority are scheduled first.
+    pub priority: Option<u32>,
+    pub user: String,
+    pub time: String,
+    /// `UNLIMITED` if the partition has no limit.
+    pub time_limit: Option<String>,
+    pub submit_time: Option<DateTime<Utc>>,
+    /// The expected start time for pending jobs, if Slurm could estimate it.
+    pub start_time: Option<DateTime<Utc>>,
+    pub tres: String,
+    pub partition: String,
+    /// Quality of service, e.g. `normal` or `debug`.
+    pub qos: String,
+    /// The account that is charged for the job.
+    pub account: String,
+    pub reservation: Option<String>,
+    /// The batch host, where the batch script runs.
+    pub submit_node: Option<String>,
+    /// Set by squeue and sacct even without `--clusters`.
+    pub cluster: Option<String>,
+    /// As printed by squeue, see [`Job::dependencies`].
+    pub dependency: Option<String>,
+    pub nodelist: String,
+    pub work_dir: PathBuf,
+    pub stdout: Option<PathBuf>,
+    pub stderr: Option<PathBuf>,
+    pub command: String,
+    pub source: JobSource,
+}
+
+impl Hash for Job {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.job_id.hash(state);
+    }
+}
+
+/// A Slurm job state such as `RUNNING`. sacct states like `CANCELLED by
+/// 1000` are matched by their first word.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JobState<'a>(&'a str);
+
+impl<'a> JobState<'a> {
+    pub fn new(state: &'a str) -> Self {
+        Self(state.split_whitespace().next().unwrap_or_default())
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.0 == "RUNNING"
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.0 == "PENDING"
+    }
+
+    pub fn is_completing(&self) -> bool {
+        self.0 == "COMPLETING"
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.0 == "FAILED"
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0 == "CANCELLED"
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        self.0 == "TIMEOUT"
+    }
+
+    pub fn is_out_of_memory(&self) -> bool {
+        self.0 == "OUT_OF_MEMORY"
+    }
+
+    /// The job has not finished yet.
+    pub fn is_active(&self) -> bool {
+        self.is_pending()
+            || self.is_running()
+            || self.is_completing()
+            || matches!(self.0, "CONFIGURING" | "SUSPENDED" | "REQUEUED")
+    }
+
+    /// The job ended because of an error rather than by itself or by scancel.
+    pub fn is_error(&self) -> bool {
+        self.is_failed()
+            || self.is_timeout()
+            || self.is_out_of_memory()
+            || matches!(self.0, "NODE_FAIL" | "BOOT_FAIL" | "DEADLINE")
+    }
+}
+
+/// Returns a field separator for `squeue --Format` that is unique to this
+/// process, so that it is practically impossible for a job name or command
+/// to contain it.
+pub fn output_separator() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(process::id());
+    format!("###turm-{:016x}###", hasher.finish())
+}
+
+/// A job ID as Slurm commands take it: `1234`, an array task `1234_5` or a range of tasks
+/// like `1234_[1-5,7]`.
+pub fn parse_job_id(s: &str) -> Result<String, String> {
+    let is_number = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    let (job, task) = s.split_once('_').unwrap_or((s, "0"));
+    let task_valid = match task.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+        Some(ranges) => ranges.split(',').all(|r| {
+            let (first, last) = r.split_once('-').unwrap_or((r, r));
+            is_number(first) && is_number(last)
+        }),
+        None => is_number(task),
+    };
+    if is_number(job) && task_valid {
+        Ok(s.to_owned())
+    } else {
+        Err(format!("`{}` is not a job ID", s))
+    }
+}
+
+/// Why a line of squeue output is not a job.
+#[derive(Debug, PartialEq, Eq)]
+pub enum JobParseError {
+    FieldCountMismatch { expected: usize, got: usize },
+    MissingField(&'static str),
+}
+
+impl fmt::Display for JobParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JobParseError::FieldCountMismatch { expected, got } => {
+                write!(f, "expected {} fields, got {}", expected, got)
+            }
+            JobParseError::MissingField(name) => write!(f, "missing {}", name),
+        }
+    }
+}
+
+impl std::error::Error for JobParseError {}
+
+impl Job {
+    /// Parses a single line of `squeue --Format` output, where each of the
+    /// [`SQUEUE_FIELDS`] is followed by `separator`.
+    pub fn from_parts(line: &str, separator: &str) -> Result<Self, JobParseError> {
+        let parts: Vec<_> = line.trim().split(separator).collect();
+
+        // every field is followed by the separator, the last part is empty
+        if parts.len() != SQUEUE_FIELDS.len() + 1 {
+            tracing::debug!(line, "unexpected number of squeue fields");
+            return Err(JobParseError::FieldCountMismatch {
+                expected: SQUEUE_FIELDS.len(),
+                got: parts.len() - 1,
+            });
+        }
+        for (i, name) in [(0, "jobid"), (2, "state"), (3, "username")] {
+            if parts[i].is_empty() {
+                tracing::debug!(line, field = name, "empty squeue field");
+                return Err(JobParseError::MissingField(name));
+            }
+        }
+
+        let id = parts[0];
+        let name = parts[1];
+        let state = parts[2];
+        let user = parts[3];
+        let time = parts[4];
+        let tres = match parts[17] {
+            "N/A" => parts[5].to_string(),
+            _ => format!("{},{}", parts[5], parts[17]),
+        };
+        let partition = parts[6];
+        let nodelist = parts[7];
+        let stdout = parts[8];
+        let stderr = parts[9];
+        let command = parts[10];
+        let state_compact = parts[11];
+        let reason = parts[12];
+
+        let array_job_id = parts[13];
+        let array_task_id = parts[14];
+        let node_list = parts[15];
+        let working_dir = parts[16];
+        let time_limit = parts[18];
+        let submit_time = parts[19];
+        let start_time = parts[20];
+        let priority = parts[21];
+        let qos = parts[22];
+        let reservation = parts[23];
+        let cluster = parts[24];
+        let dependency = parts[25];
+        let account = parts[26];
+        let batch_host = parts[27];
+
+        Ok(Job {
+            job_id: id.to_owned(),
+            array_id: array_job_id.to_owned(),
+            array_step: match array_task_id {
+                "N/A" => None,
+                _ => Some(array_task_id.to_owned()),
+            },
+            name: name.to_owned(),
+            state: state.to_owned(),
+            state_compact: state_compact.to_owned(),
+            reason: JobReason::parse(reason),
+            priority: priority.parse().ok(),
+            user: user.to_owned(),
+            time: time.to_owned(),
+            time_limit: parse_time_limit(time_limit),
+            submit_time: parse_slurm_time(submit_time),
+            start_time: parse_slurm_time(start_time),
+            tres,
+            partition: partition.to_owned(),
+            qos: qos.to_owned(),
+            account: account.to_owned(),
+            reservation: parse_optional(reservation),
+            cluster: parse_optional(cluster),
+            dependency: parse_optional(dependency),
+            submit_node: parse_optional(batch_host),
+            nodelist: nodelist.to_owned(),
+            work_dir: PathBuf::from(working_dir),
+            command: command.to_owned(),
+            stdout: Self::resolve_path(
+                stdout,
+                array_job_id,
+                array_task_id,
+                id,
+                node_list,
+                user,
+                name,
+                working_dir,
+            ),
+            stderr: Self::resolve_path(
+                stderr,
+                array_job_id,
+                array_task_id,
+                id,
+                node_list,
+                user,
+                name,
+                working_dir,
+            ), // TODO fill all fields
+            source: JobSource::Squeue,
+        })
+    }
+
+    /// Parses a single line of `sacct --parsable2` output, where `fields` are
+    /// the names passed to `sacct --format` (case insensitive). Only
+    /// `JobID` is required.
+    pub fn from_sacct_line(line: &str, fields: &[&str]) -> Option<Self> {
+        let parts: Vec<_> = line.split('|').collect();
+        if parts.len() != fields.len() {
+            return None;
+        }
+        let get = |name: &str| {
+            fields
+                .iter()
+                .position(|f| f.eq_ignore_ascii_case(name))
+                .map(|i| parts[i])
+        };
+
+        // array tasks are reported as `<ArrayJobID>_<ArrayTaskID>`, with their
+        // own job id in `JobIDRaw`
+        let job_id = get("JobID")?;
+        let (array_id, array_task_id) = job_id.split_once('_').unwrap_or((job_id, "N/A"));
+        let id = get("JobIDRaw").unwrap_or(array_id);
+        let name = get("JobName").unwrap_or_default();
+        // e.g. `CANCELLED by 1000`
+        let state = get("State")
+            .and_then(|s| s.split(' ').next())
+            .unwrap_or_default();
+        let user = get("User").unwrap_or_default();
+        let nodelist = match get("NodeList").unwrap_or_default() {
+            "None assigned" => "",
+            n => n,
+        };
+        let working_dir = get("WorkDir").unwrap_or_default();
+
+        // sacct does not know the output paths, assume the sbatch default
+        let output = Self::resolve_path(
+            "",
+            array_id,
+            array_task_id,
+            id,
+            nodelist,
+            user,
+            name,
+            working_dir,
+        );
+
+        Some(Job {
+            job_id: id.to_owned(),
+            array_id: array_id.to_owned(),
+            array_step: match array_task_id {
+                "N/A" => None,
+                _ => Some(array_task_id.to_owned()),
+            },
+            name: name.to_owned(),
+            state: state.to_owned(),
+            state_compact: state_compact(state).to_owned(),
+            reason: get("Reason").and_then(JobReason::parse),
+            priority: get("Priority").and_then(|p| p.parse().ok()),
+            user: user.to_owned(),
+            time: get("Elapsed").unwrap_or_default().to_owned(),
+            time_limit: get("Timelimit").and_then(parse_time_limit),
+            submit_time: get("Submit").and_then(parse_slurm_time),
+            start_time: get("Start").and_then(parse_slurm_time),
+            tres: get("AllocTRES").unwrap_or_default().to_owned(),
+            partition: get("Partition").unwrap_or_default().to_owned(),
+            qos: get("QOS").unwrap_or_default().to_owned(),
+            account: get("Account").unwrap_or_default().to_owned(),
+            reservation: get("Reservation").and_then(parse_optional),
+            cluster: get("Cluster").and_then(parse_optional),
+            dependency: None,
+            submit_node: None,
+            nodelist: nodelist.to_owned(),
+            work_dir: PathBuf::from(working_dir),
+            stdout: output.clone(),
+            stderr: output,
+            command: get("SubmitLine").unwrap_or_default().to_owned(),
+            source: JobSource::Sacct,
+        })
+    }
+
+    pub fn id(&self) -> String {
+        match self.array_step.as_ref() {
+            Some(array_step) => format!("{}_{}", self.array_id, array_step),
+            None => self.job_id.clone(),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        JobState::new(&self.state).is_running()
+    }
+
+    pub fn is_pending(&self) -> bool {
+        JobState::new(&self.state).is_pending()
+    }
+
+    pub fn is_completing(&self) -> bool {
+        JobState::new(&self.state).is_completing()
+    }
+
+    pub fn is_failed(&self) -> bool {
+        JobState::new(&self.state).is_failed()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        JobState::new(&self.state).is_cancelled()
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        JobState::new(&self.state).is_timeout()
+    }
+
+    pub fn is_out_of_memory(&self) -> bool {
+        JobState::new(&self.state).is_out_of_memory()
+    }
+
+    /// `None` if Slurm did not report it.
+    pub fn workdir(&self) -> Option<&Path> {
+        Some(self.work_dir.as_path()).filter(|p| !p.as_os_str().is_empty())
+    }
+
+    /// The expanded `nodelist`.
+    pub fn nodes(&self) -> Vec<String> {
+        nodelist::expand(&self.nodelist)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes().len()
+    }
+
+    pub fn elapsed_seconds(&self) -> Option<u64> {
+        parse_slurm_duration(&self.time)
+    }
+
+    /// `None` for jobs without a (finite) limit.
+    pub fn time_limit_seconds(&self) -> Option<u64> {
+        self.time_limit.as_deref().and_then(parse_slurm_duration)
+    }
+
+    pub fn time_remaining_seconds(&self) -> Option<u64> {
+        Some(
+            self.time_limit_seconds()?
+                .saturating_sub(self.elapsed_seconds()?),
+        )
+    }
+
+    /// How long a job that has started waited in the queue.
+    pub fn wait_duration(&self) -> Option<TimeDelta> {
+        if self.is_pending() {
+            return None;
+        }
+        Some(self.start_time? - self.submit_time?)
+    }
+
+    /// How long a pending job has been waiting so far.
+    pub fn wait_so_far(&self) -> Option<TimeDelta> {
+        if !self.is_pending() {
+            return None;
+        }
+        Some(Utc::now() - self.submit_time?)
+    }
+
+    pub fn dependencies(&self) -> Vec<Dependency> {
+        self.dependency
+            .as_deref()
+            .map(parse_dependencies)
+            .unwrap_or_default()
+    }
+
+    pub fn tres_parsed(&self) -> Option<TresResources> {
+        TresResources::parse(&self.tres)
+    }
+
+    /// The allocated CPUs, 0 if the TRES can't be parsed.
+    pub fn cpus(&self) -> u32 {
+        self.tres_parsed().map_or(0, |t| t.cpu)
+    }
+
+    pub fn gpu_count(&self) -> Option<u32> {
+        self.tres_parsed().and_then(|t| t.gpu).map(|g| g.count)
+    }
+
+    /// Running with GPUs allocated for longer than `seconds`, worth a look in
+    /// case they sit idle.
+    pub fn gpu_running_longer_than(&self, seconds: u64) -> bool {
+        self.is_running()
+            && self.gpu_count().is_some_and(|c| c > 0)
+            && self.elapsed_seconds().is_some_and(|e| e > seconds)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn resolve_path(
+        path: &str,
+        array_master: &str,
+        array_id: &str,
+        id: &str,
+        host: &str,
+        user: &str,
+        name: &str,
+        working_dir: &str,
+    ) -> Option<PathBuf> {
+        // see https://slurm.schedmd.com/sbatch.html#SECTION_%3CB%3Efilename-pattern%3C/B%3E
+        static RE: OnceLock<Regex> = OnceLock::new();
+        let re = RE.get_or_init(|| Regex::new(r"%(%|A|a|J|j|N|n|s|t|u|x)").unwrap());
+
+        let mut path = path.to_owned();
+        let slurm_no_val = "4294967294";
+        let array_id = if array_id == "N/A" {
+            slurm_no_val
+        } else {
+            array_id
+        };
+
+        // e.g. interactive jobs of salloc have no output file
+        if path == "(null)" {
+            return None;
+        }
+        if path.is_empty() {
+            // never happens right now, because `squeue -O stdout` seems to always return something
+            path = if array_id == slurm_no_val {
+                PathBuf::from(working_dir).join("slurm-%J.out")
+            } else {
+                PathBuf::from(working_dir).join("slurm-%A_%a.out")
+            }
+            .to_str()
+            .unwrap()
+            .to_owned();
+        };
+
+        for cap in re
+            .captures_iter(&path.clone())
+            .collect::<Vec<_>>() // TODO: this is stupid, there has to be a better way to reverse the captures...
+            .iter()
+            .rev()
+        {
+            let m = cap.get(0).unwrap();
+            let replacement = match m.as_str() {
+                "%%" => "%",
+                "%A" => array_master,
+                "%a" => array_id,
+                "%J" => id,
+                "%j" => id,
+                "%N" => host.split(',').next().unwrap_or(host),
+                "%n" => "0",
+                "%s" => "batch",
+                "%t" => "0",
+                "%u" => user,
+                "%x" => name,
+                _ => unreachable!(),
+            };
+
+            path.replace_range(m.range(), replacement);
+        }
+
+        Some(PathBuf::from(working_dir).join(path)) // works even if `path` is absolute
+    }
+}
+
+/// A line of squeue output for a running job, each of `fields` is a
+/// `(name, value)` of [`SQUEUE_FIELDS`] that replaces the default. The
+/// `ArrayJobID` defaults to the `jobid`.
+#[cfg(test)]
+pub fn test_squeue_line(separator: &str, fields: &[(&str, &str)]) -> String {
+    for (name, _) in fields {
+        assert!(SQUEUE_FIELDS.contains(name), "unknown field {}", name);
+    }
+    let get = |name: &str| fields.iter().find(|(n, _)| *n == name).map(|(_, v)| *v);
+    let defaults = [
+        "1234",
+        "train",
+        "RUNNING",
+        "alice",
+        "1:23",
+        "cpu=4,mem=16G,node=1,billing=4",
+        "gpu",
+        "node01",
+        "/home/alice/slurm-%j.out",
+        "/home/alice/slurm-%j.err",
+        "/home/alice/job.sh",
+        "R",
+        "None",
+        get("jobid").unwrap_or("1234"),
+        "N/A",
+        "node01",
+        "/home/alice",
+        "N/A",
+        "1-00:00:00",
+        "2024-05-01T10:00:00",
+        "2024-05-01T10:05:00",
+        "N/A",
+        "normal",
+        "(null)",
+        "hpc1",
+        "afterok:1200(unfulfilled)",
+        "proj1",
+        "node01",
+    ];
+    SQUEUE_FIELDS
+        .iter()
+        .zip(defaults)
+        .map(|(name, default)| format!("{}{}", get(name).unwrap_or(default), separator))
+        .collect()
+}
+
+#[cfg(test)]
+impl Job {
+    /// A running job of alice without TRES, tests override what they need
+    /// with `..Job::test_default()`.
+    pub fn test_default() -> Self {
+        Self {
+            job_id: "1".to_owned(),
+            array_id: "1".to_owned(),
+            array_step: None,
+            name: "job".to_owned(),
+            state: "RUNNING".to_owned(),
+            state_compact: "R".to_owned(),
+            reason: None,
+            priority: None,
+            user: "alice".to_owned(),
+            time: "0:00".to_owned(),
+            time_limit: None,
+            submit_time: None,
+            start_time: None,
+            tres: "N/A".to_owned(),
+            partition: "gpu".to_owned(),
+            qos: "normal".to_owned(),
+            account: "proj1".to_owned(),
+            reservation: None,
+            cluster: None,
+            dependency: None,
+            submit_node: None,
+            nodelist: "".to_owned(),
+            work_dir: PathBuf::new(),
+            stdout: None,
+            stderr: None,
+            command: "".to_owned(),
+            source: JobSource::Squeue,
+        }
+    }
+}
+
+impl fmt::Display for Job {
+    /// Single line summary, e.g. `123456 (myname) R 2:15:00 cpu=4,mem=16G node01`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}) {} {} {} {}",
+            self.id(),
+            self.name,
+            self.state_compact,
+            self.time,
+            self.tres,
+            self.nodelist
+        )
+    }
+}
+
+/// The abbreviation `squeue` prints as `statecompact`.
+fn state_compact(state: &str) -> &str {
+    match state {
+        "BOOT_FAIL" => "BF",
+        "CANCELLED" => "CA",
+        "COMPLETED" => "CD",
+        "COMPLETING" => "CG",
+        "DEADLINE" => "DL",
+        "FAILED" => "F",
+        "NODE_FAIL" => "NF",
+        "OUT_OF_MEMORY" => "OOM",
+        "PENDING" => "PD",
+        "PREEMPTED" => "PR",
+        "REQUEUED" => "RQ",
+        "RESIZING" => "RS",
+        "REVOKED" => "RV",
+        "RUNNING" => "R",
+        "SUSPENDED" => "S",
+        "TIMEOUT" => "TO",
+        other => other,
+    }
+}
+
+fn parse_time_limit(s: &str) -> Option<String> {
+    match s {
+        "" | "N/A" | "NOT_SET" | "INVALID" => None,
+        _ => Some(s.to_owned()),
+    }
+}
+
+/// `None` for the placeholders of unset fields.
+fn parse_optional(s: &str) -> Option<String> {
+    match s {
+        "" | "N/A" | "(null)" => None,
+        _ => Some(s.to_owned()),
+    }
+}
+
+/// Parses a Slurm timestamp, e.g. `2024-05-01T10:00:00`. Slurm prints them
+/// in the local time zone.
+fn parse_slurm_time(s: &str) -> Option<DateTime<Utc>> {
+    let time = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S").ok()?;
+    Local
+        .from_local_datetime(&time)
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Parses a Slurm duration (`[D-]HH:MM:SS` or `MM:SS`) into seconds.
+fn parse_slurm_duration(s: &str) -> Option<u64> {
+    let (days, hms) = match s.split_once('-') {
+        Some((d, hms)) => (d.parse::<u64>().ok()?, hms),
+        None => (0, s),
+    };
+    let parts = hms
+        .split(':')
+        .map(|p| p.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let (hours, minutes, seconds) = match parts[..] {
+        [h, m, s] => (h, m, s),
+        [m, s] => (0, m, s),
+        _ => return None,
+    };
+    Some(((days * 24 + hours) * 60 + minutes) * 60 + seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_from_parts() {
+        let separator = output_separator();
+        let job = Job::from_parts(&test_squeue_line(&separator, &[]), &separator).unwrap();
+        assert_eq!(job.id(), "1234");
+        assert_eq!(job.name, "train");
+        assert_eq!(job.state, "RUNNING");
+        assert_eq!(job.reason, None);
+        assert_eq!(job.priority, None);
+        assert_eq!(job.qos, "normal");
+        assert_eq!(job.account, "proj1");
+        assert_eq!(job.submit_node.as_deref(), Some("node01"));
+        assert_eq!(job.reservation, None);
+        assert_eq!(job.cluster.as_deref(), Some("hpc1"));
+        assert_eq!(job.dependencies()[0].job_ids, ["1200"]);
+        assert_eq!(job.tres, "cpu=4,mem=16G,node=1,billing=4");
+        assert_eq!(job.cpus(), 4);
+        let pending = Job {
+            tres: "N/A".to_owned(),
+            ..job.clone()
+        };
+        assert_eq!(pending.cpus(), 0);
+        assert!(!job.gpu_running_longer_than(60));
+        let gpu = Job {
+            tres: "cpu=4,mem=16G,node=1,billing=4,gres/gpu=2".to_owned(),
+            ..job.clone()
+        };
+        assert!(gpu.gpu_running_longer_than(60));
+        assert!(!gpu.gpu_running_longer_than(300));
+        assert_eq!(job.time_limit.as_deref(), Some("1-00:00:00"));
+        assert_eq!(job.time_limit_seconds(), Some(86400));
+        assert_eq!(job.time_remaining_seconds(), Some(86400 - 83));
+        assert_eq!(job.workdir(), Some(Path::new("/home/alice")));
+        assert_eq!(job.nodes(), ["node01"]);
+        assert_eq!(
+            job.submit_time
+                .map(|t| t.with_timezone(&Local).naive_local()),
+            NaiveDateTime::from_str("2024-05-01T10:00:00").ok()
+        );
+        assert_eq!(
+            job.stdout,
+            Some(PathBuf::from("/home/alice/slurm-1234.out"))
+        );
+
+        assert_eq!(
+            Job::from_parts("1234###turm###train###turm###", &separator).err(),
+            Some(JobParseError::FieldCountMismatch {
+                expected: SQUEUE_FIELDS.len(),
+                got: 0
+            })
+        );
+        let line = test_squeue_line(&separator, &[]).replacen("alice", "", 1);
+        assert_eq!(
+            Job::from_parts(&line, &separator).err(),
+            Some(JobParseError::MissingField("username"))
+        );
+    }
+
+    #[test]
+    fn test_from_sacct_line() {
+        let fields = [
+            "JobID",
+            "JobIDRaw",
+            "JobName",
+            "State",
+            "User",
+            "Elapsed",
+            "AllocTRES",
+            "Partition",
+            "NodeList",
+            "WorkDir",
+        ];
+
+        let job = Job::from_sacct_line(
+            "1234|1234|train|COMPLETED|alice|00:10:00|cpu=4,mem=16G,node=1|gpu|node01|/home/alice",
+            &fields,
+        )
+        .unwrap();
+        assert_eq!(job.id(), "1234");
+        assert_eq!(job.state_compact, "CD");
+        assert_eq!(job.time, "00:10:00");
+        assert_eq!(
+            job.stdout,
+            Some(PathBuf::from("/home/alice/slurm-1234.out"))
+        );
+
+        let job = Job::from_sacct_line(
+            "1234_7|1241|train|CANCELLED by 1000|alice|00:00:00|||None assigned|/home/alice",
+            &fields,
+        )
+        .unwrap();
+        assert_eq!(job.id(), "1234_7");
+        assert_eq!(job.job_id, "1241");
+        assert_eq!(job.state, "CANCELLED");
+        assert_eq!(job.nodelist, "");
+        assert_eq!(
+            job.stdout,
+            Some(PathBuf::from("/home/alice/slurm-1234_7.out"))
+        );
+
+        // field order and case do not matter, missing fields are empty
+        let job = Job::from_sacct_line(
+            "RUNNING|1234_[1-5]|Resources|4294|maint",
+            &["state", "jobid", "reason", "priority", "reservation"],
+        )
+        .unwrap();
+        assert_eq!(job.id(), "1234_[1-5]");
+        assert_eq!(job.job_id, "1234");
+        assert_eq!(job.reason, Some(JobReason::Resources));
+        assert_eq!(job.priority, Some(4294));
+        assert_eq!(job.reservation.as_deref(), Some("maint"));
+        assert_eq!(job.workdir(), None);
+
+        assert!(Job::from_sacct_line("1234|train", &fields).is_none());
+        assert!(Job::from_sacct_line("train", &["JobName"]).is_none());
+    }
+
+    #[test]
+    fn test_squeue_and_sacct_paths_agree() {
+        // squeue without an explicit output path falls back to the same
+        // sbatch default that is assumed for sacct jobs
+        let separator = output_separator();
+        let line = test_squeue_line(&separator, &[]).replace("/home/alice/slurm-%j.out", "");
+        let squeue_job = Job::from_parts(&line, &separator).unwrap();
+        let sacct_job = Job::from_sacct_line(
+            "1234|1234|train|RUNNING|alice|node01|/home/alice",
+            &[
+                "JobID", "JobIDRaw", "JobName", "State", "User", "NodeList", "WorkDir",
+            ],
+        )
+        .unwrap();
+        assert_eq!(squeue_job.stdout, sacct_job.stdout);
+        assert_eq!(
+            squeue_job.stdout,
+            Some(PathBuf::from("/home/alice/slurm-1234.out"))
+        );
+    }
+
+    #[test]
+    fn test_state_predicates() {
+        let separator = output_separator();
+        let mut job = Job::from_parts(&test_squeue_line(&separator, &[]), &separator).unwrap();
+        assert!(job.is_running());
+        assert!(!job.is_pending());
+
+        job.state = "PENDING".to_string();
+        assert!(job.is_pending());
+        assert!(!job.is_running());
+
+        job.state = "OUT_OF_MEMORY".to_string();
+        assert!(job.is_out_of_memory());
+        assert!(!job.is_failed());
+
+        job.state = "CANCELLED by 1000".to_string();
+        assert!(job.is_cancelled());
+
+        assert!(JobState::new("REQUEUED").is_active());
+        assert!(!JobState::new("COMPLETED").is_active());
+        assert!(JobState::new("NODE_FAIL").is_error());
+        assert!(!JobState::new("CANCELLED").is_error());
+    }
+
+    #[test]
+    fn test_display() {
+        let separator = output_separator();
+        let job = Job::from_parts(&test_squeue_line(&separator, &[]), &separator).unwrap();
+        assert_eq!(
+            job.to_string(),
+            "1234 (train) R 1:23 cpu=4,mem=16G,node=1,billing=4 node01"
+        );
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let separator = output_separator();
+        let job = Job::from_parts(&test_squeue_line(&separator, &[]), &separator).unwrap();
+        let json = serde_json::to_string(&job).unwrap();
+        let read: Job = serde_json::from_str(&json).unwrap();
+        assert_eq!(read, job);
+    }
+
+    #[test]
+    fn test_hash_by_job_id() {
+        let separator = output_separator();
+        let job = Job::from_parts(&test_squeue_line(&separator, &[]), &separator).unwrap();
+        let completing = Job {
+            state: "COMPLETING".to_owned(),
+            ..job.clone()
+        };
+        let state = RandomState::new();
+        assert_eq!(state.hash_one(&job), state.hash_one(&completing));
+        assert_ne!(job, completing);
+
+        let jobs = HashSet::from([job.clone(), completing.clone(), job]);
+        assert_eq!(jobs.len(), 2);
+        assert!(jobs.contains(&completing));
+    }
+
+    #[test]
+    fn test_from_parts_name_containing_old_separator() {
+        let separator = output_separator();
+        let name = "my###turm###job";
+        let job =
+            Job::from_parts(&test_squeue_line(&separator, &[("name", name)]), &separator).unwrap();
+        assert_eq!(job.name, name);
+    }
+
+    fn resolve(path: &str, array_id: &str) -> Option<PathBuf> {
+        Job::resolve_path(
+            path,
+            "1234",
+            array_id,
+            "1239",
+            "node01,node02",
+            "alice",
+            "train",
+            "/home/alice",
+        )
+    }
+
+    #[test]
+    fn test_resolve_path_patterns() {
+        let cases = [
+            ("%A", "1234"),
+            ("%a", "5"),
+            ("%J", "1239"),
+            ("%j", "1239"),
+            ("%N", "node01"),
+            ("%n", "0"),
+            ("%s", "batch"),
+            ("%t", "0"),
+            ("%u", "alice"),
+            ("%x", "train"),
+        ];
+        for (pattern, value) in cases {
+            assert_eq!(
+                resolve(&format!("out-{}.log", pattern), "5"),
+                Some(PathBuf::from(format!("/home/alice/out-{}.log", value))),
+                "{}",
+                pattern
+            );
+        }
+        // not a pattern
+        assert_eq!(
+            resolve("%q.out", "5"),
+            Some(PathBuf::from("/home/alice/%q.out"))
+        );
+    }
+
+    #[test]
+    fn test_resolve_path_combined() {
+        assert_eq!(
+            resolve("logs/%x-%A_%a.%u.out", "5"),
+            Some(PathBuf::from("/home/alice/logs/train-1234_5.alice.out"))
+        );
+        assert_eq!(
+            resolve("%j%j%N", "5"),
+            Some(PathBuf::from("/home/alice/12391239node01"))
+        );
+    }
+
+    #[test]
+    fn test_resolve_path_escape() {
+        assert_eq!(
+            resolve("100%%.out", "5"),
+            Some(PathBuf::from("/home/alice/100%.out"))
+        );
+        // the escaped `%` does not start a pattern
+        assert_eq!(
+            resolve("%%j-%j", "5"),
+            Some(PathBuf::from("/home/alice/%j-1239"))
+        );
+    }
+
+    #[test]
+    fn test_resolve_path_absolute() {
+        assert_eq!(
+            resolve("/scratch/%u/%j.out", "5"),
+            Some(PathBuf::from("/scratch/alice/1239.out"))
+        );
+        assert_eq!(
+            resolve("out/%j.out", "5"),
+            Some(PathBuf::from("/home/alice/out/1239.out"))
+        );
+    }
+
+    #[test]
+    fn test_resolve_path_missing() {
+        assert_eq!(resolve("(null)", "5"), None);
+        // sbatch's default
+        assert_eq!(
+            resolve("", "N/A"),
+            Some(PathBuf::from("/home/alice/slurm-1239.out"))
+        );
+        assert_eq!(
+            resolve("", "5"),
+            Some(PathBuf::from("/home/alice/slurm-1234_5.out"))
+        );
+    }
+
+    #[test]
+    fn test_resolve_path_not_an_array() {
+        // Slurm's NO_VAL, like sbatch
+        assert_eq!(
+            resolve("%A_%a.out", "N/A"),
+            Some(PathBuf::from("/home/alice/1234_4294967294.out"))
+        );
+    }
+
+    #[test]
+    fn test_wait_duration() {
+        let separator = output_separator();
+        let mut job = Job::from_parts(&test_squeue_line(&separator, &[]), &separator).unwrap();
+        assert_eq!(job.wait_duration(), Some(TimeDelta::minutes(5)));
+        assert_eq!(job.wait_so_far(), None);
+
+        // the start time of pending jobs is only an estimate
+        job.state = "PENDING".to_string();
+        assert_eq!(job.wait_duration(), None);
+        assert!(job.wait_so_far().unwrap() > TimeDelta::days(365));
+    }
+
+    #[test]
+    fn test_parse_job_id() {
+        for id in ["1234", "1234_5", "1234_[1-5]", "1234_[1,3-5]"] {
+            assert_eq!(parse_job_id(id).as_deref(), Ok(id));
+        }
+        for id in ["", "abc", "1234_", "1234_[1-]", "1234_[1-5", "_5"] {
+            assert!(parse_job_id(id).is_err(), "{}", id);
+        }
+    }
+
+    #[test]
+    fn test_parse_slurm_time() {
+        assert!(parse_slurm_time("2024-05-01T10:00:00").is_some());
+        assert_eq!(parse_slurm_time("N/A"), None);
+        assert_eq!(parse_slurm_time("Unknown"), None);
+    }
+
+    #[test]
+    fn test_parse_slurm_duration() {
+        assert_eq!(parse_slurm_duration("0:05"), Some(5));
+        assert_eq!(parse_slurm_duration("12:34"), Some(12 * 60 + 34));
+        assert_eq!(
+            parse_slurm_duration("2:34:56"),
+            Some(2 * 3600 + 34 * 60 + 56)
+        );
+        assert_eq!(
+            parse_slurm_duration("3-02:15:00"),
+            Some(3 * 86400 + 2 * 3600 + 15 * 60)
+        );
+
+        // Unparseable
+        assert_eq!(parse_slurm_duration("INVALID"), None);
+        assert_eq!(parse_slurm_duration(""), None);
+        assert_eq!(parse_slurm_duration("1:2:3:4"), None);
+        assert_eq!(parse_slurm_duration("x-01:00:00"), None);
+    }
+}
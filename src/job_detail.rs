@@ -0,0 +1,122 @@
+use std::{collections::HashMap, path::Path, process::Command};
+
+/// Shown first in this order, the remaining fields follow alphabetically.
+const FIELDS_OF_INTEREST: [&str; 9] = [
+    "SubmitTime",
+    "StartTime",
+    "EndTime",
+    "WorkDir",
+    "StdOut",
+    "StdErr",
+    "NumCPUs",
+    "NumNodes",
+    "TRES",
+];
+
+/// The output of `scontrol show job`.
+pub struct JobDetail {
+    pub job_id: String,
+    pub fields: HashMap<String, String>,
+}
+
+impl JobDetail {
+    pub fn fetch(scontrol: &Path, job_id: &str) -> Result<Self, String> {
+        let output = Command::new(scontrol)
+            .args(["show", "job", job_id])
+            .output()
+            .map_err(|e| format!("Failed to execute {}: {}", scontrol.display(), e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "scontrol failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        Ok(Self {
+            job_id: job_id.to_owned(),
+            fields: parse_scontrol(&String::from_utf8_lossy(&output.stdout)),
+        })
+    }
+
+    pub fn sorted_fields(&self) -> Vec<(&str, &str)> {
+        let mut rest: Vec<_> = self
+            .fields
+            .iter()
+            .filter(|(k, _)| !FIELDS_OF_INTEREST.contains(&k.as_str()))
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        rest.sort();
+
+        FIELDS_OF_INTEREST
+            .iter()
+            .filter_map(|&k| self.fields.get(k).map(|v| (k, v.as_str())))
+            .chain(rest)
+            .collect()
+    }
+}
+
+/// Parses whitespace separated `Key=Value` pairs. Words without `=` belong to
+/// the previous value, e.g. `Command=./train.sh --epochs 10 --lr=0.1`. Only the first
+/// record is read, scontrol prints one per array task.
+fn parse_scontrol(output: &str) -> HashMap<String, String> {
+    let mut fields = HashMap::new();
+    let mut last_key: Option<String> = None;
+
+    let record = output.trim().split("\n\n").next().unwrap_or_default();
+    for word in record.split_whitespace() {
+        match word.split_once('=') {
+            Some((k, v)) if k.starts_with(|c: char| c.is_ascii_uppercase()) => {
+                fields.insert(k.to_owned(), v.to_owned());
+                last_key = Some(k.to_owned());
+            }
+            _ => {
+                if let Some(v) = last_key.as_ref().and_then(|k| fields.get_mut(k)) {
+                    v.push(' ');
+                    v.push_str(word);
+                }
+            }
+        }
+    }
+
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scontrol() {
+        let output = "JobId=1001 JobName=train
+   UserId=alice(1000) GroupId=alice(1000) MCS_label=N/A
+   JobState=RUNNING Reason=None Dependency=(null)
+   SubmitTime=2024-05-01T10:00:00 EligibleTime=2024-05-01T10:00:00
+   NumNodes=1 NumCPUs=8 NumTasks=1 CPUs/Task=8 ReqB:S:C:T=0:0:*:*
+   TRES=cpu=8,mem=32G,node=1,billing=8,gres/gpu=2
+   Command=/home/alice/train.sh --epochs 10 --lr=0.1
+   WorkDir=/home/alice
+   StdOut=/home/alice/slurm-1001.out
+
+JobId=1002 JobName=other
+";
+        let fields = parse_scontrol(output);
+        assert_eq!(fields["JobId"], "1001");
+        assert_eq!(fields["UserId"], "alice(1000)");
+        assert_eq!(fields["TRES"], "cpu=8,mem=32G,node=1,billing=8,gres/gpu=2");
+        assert_eq!(
+            fields["Command"],
+            "/home/alice/train.sh --epochs 10 --lr=0.1"
+        );
+        assert_eq!(fields["CPUs/Task"], "8");
+        assert_eq!(fields["ReqB:S:C:T"], "0:0:*:*");
+
+        let detail = JobDetail {
+            job_id: "1001".to_owned(),
+            fields,
+        };
+        let sorted = detail.sorted_fields();
+        assert_eq!(sorted[0], ("SubmitTime", "2024-05-01T10:00:00"));
+        assert_eq!(sorted[1], ("WorkDir", "/home/alice"));
+        assert_eq!(sorted[6].0, "CPUs/Task");
+    }
+}
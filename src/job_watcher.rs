@@ -1,214 +1,646 @@
-use std::path::PathBuf;
-use std::{io::BufRead, process::Command, thread, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs,
+    iter::once,
+    path::{Path, PathBuf},
+    process::Command,
+    thread::{self, JoinHandle},
+    time::Duration,
+};
 
-use crossbeam::channel::Sender;
-use regex::Regex;
+use crossbeam::{
+    channel::{unbounded, Receiver, RecvError, SendError, Sender},
+    select,
+};
 
 use crate::app::AppMessage;
-use crate::app::Job;
+use crate::diff::{jobs_changed, JobChange};
+use crate::filter::JobFilter;
+use crate::job::{parse_job_id, Job, JobParseError, JobState};
+use crate::sacct_watcher::sacct_states;
+use crate::squeue_parser::SqueueParser;
 
 struct JobWatcher {
     app: Sender<AppMessage>,
+    receiver: Receiver<WatcherCommand>,
+    config: JobWatcherConfig,
+    parser: SqueueParser,
+    /// The jobs of the last poll, `None` before the first one.
+    prev_jobs: Option<Vec<Job>>,
+    /// Consecutive squeue failures. Unknown job IDs are no failure, squeue
+    /// would fail for them on every poll.
+    failures: u32,
+    /// Jobs of the `watch_file` that squeue doesn't know anymore.
+    unknown_ids: HashSet<String>,
+}
+
+/// How long to wait after squeue failed.
+#[derive(Clone, Copy, Debug)]
+pub struct Backoff {
+    pub max_delay: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            max_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+impl Backoff {
+    /// Doubles with every failure, starting at `interval`.
+    fn delay(&self, interval: Duration, failures: u32) -> Duration {
+        match failures {
+            0 => interval,
+            n => interval
+                .saturating_mul(2u32.saturating_pow(n - 1))
+                .min(self.max_delay.max(interval)),
+        }
+    }
+}
+
+/// Settings of the job watcher, e.g.
+/// `JobWatcherConfig::default().interval(Duration::from_secs(5))`.
+#[derive(Clone, Debug)]
+pub struct JobWatcherConfig {
     interval: Duration,
     squeue_args: Vec<String>,
+    watch_file: Option<PathBuf>,
+    backoff: Backoff,
+    /// Give up after this many consecutive failures.
+    max_retries: u32,
+    /// How far back sacct looks for finished jobs, `None` if it is off. The
+    /// sacct watcher is started by the TUI next to this one.
+    sacct: Option<Duration>,
+    /// The initial filter of the TUI, which can change it. Jobs are always
+    /// reported unfiltered, so a wider filter shows them again.
+    filter: JobFilter,
+    squeue_binary: PathBuf,
+    /// Only the most recently submitted jobs are reported.
+    max_jobs: Option<usize>,
+    /// Not run by the watcher, but by the TUI to show and hold jobs.
+    scontrol_binary: PathBuf,
+    /// Not run by the watcher either, cancels jobs.
+    scancel_binary: PathBuf,
+}
+
+impl Default for JobWatcherConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(2),
+            squeue_args: Vec::new(),
+            watch_file: None,
+            backoff: Backoff::default(),
+            max_retries: 10,
+            sacct: None,
+            filter: JobFilter::default(),
+            squeue_binary: PathBuf::from("squeue"),
+            max_jobs: None,
+            scontrol_binary: PathBuf::from("scontrol"),
+            scancel_binary: PathBuf::from("scancel"),
+        }
+    }
+}
+
+impl JobWatcherConfig {
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn squeue_args(mut self, squeue_args: Vec<String>) -> Self {
+        self.squeue_args = squeue_args;
+        self
+    }
+
+    pub fn watch_file(mut self, watch_file: Option<PathBuf>) -> Self {
+        self.watch_file = watch_file;
+        self
+    }
+
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Also shows the jobs sacct reports as finished within `since`.
+    pub fn sacct_enabled(mut self, since: Option<Duration>) -> Self {
+        self.sacct = since;
+        self
+    }
+
+    pub fn filter(mut self, filter: JobFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn squeue_binary(mut self, path: PathBuf) -> Self {
+        self.squeue_binary = path;
+        self
+    }
+
+    pub fn max_jobs(mut self, max_jobs: Option<usize>) -> Self {
+        self.max_jobs = max_jobs;
+        self
+    }
+
+    pub fn scontrol_binary(mut self, path: PathBuf) -> Self {
+        self.scontrol_binary = path;
+        self
+    }
+
+    pub fn scancel_binary(mut self, path: PathBuf) -> Self {
+        self.scancel_binary = path;
+        self
+    }
+
+    pub fn scontrol(&self) -> &Path {
+        &self.scontrol_binary
+    }
+
+    pub fn scancel(&self) -> &Path {
+        &self.scancel_binary
+    }
+
+    pub fn sacct_since(&self) -> Option<Duration> {
+        self.sacct
+    }
+
+    pub fn job_filter(&self) -> &JobFilter {
+        &self.filter
+    }
 }
 
-pub struct JobWatcherHandle {}
+pub enum WatcherCommand {
+    /// Poll immediately instead of waiting for the next interval.
+    PollNow,
+    /// Stop the watcher thread.
+    Shutdown,
+}
+
+pub struct JobWatcherHandle {
+    sender: Sender<WatcherCommand>,
+    thread: Option<JoinHandle<Result<(), RecvError>>>,
+}
 
 impl JobWatcher {
-    fn new(app: Sender<AppMessage>, interval: Duration, squeue_args: Vec<String>) -> Self {
+    fn new(
+        app: Sender<AppMessage>,
+        receiver: Receiver<WatcherCommand>,
+        config: JobWatcherConfig,
+    ) -> Self {
         Self {
             app,
-            interval,
-            squeue_args,
+            receiver,
+            config,
+            parser: SqueueParser::default(),
+            prev_jobs: None,
+            failures: 0,
+            unknown_ids: HashSet::new(),
         }
     }
 
-    fn run(&mut self) -> Self {
-        let output_separator = "###turm###";
-        let fields = [
-            "jobid",
-            "name",
-            "state",
-            "username",
-            "timeused",
-            "tres-alloc",
-            "partition",
-            "nodelist",
-            "stdout",
-            "stderr",
-            "command",
-            "statecompact",
-            "reason",
-            "ArrayJobID",  // %A
-            "ArrayTaskID", // %a
-            "NodeList",    // %N
-            "WorkDir",     // for fallback
-            "tres-per-node",     
-        ];
-        let output_format = fields
-            .map(|s| s.to_owned() + ":" + output_separator)
-            .join(",");
-
+    fn run(&mut self) -> Result<(), RecvError> {
+        let _span = tracing::info_span!("job_watcher").entered();
+        tracing::info!(interval = ?self.config.interval, args = ?self.config.squeue_args, "started");
         loop {
-            let jobs: Vec<Job> = Command::new("squeue")
-                .args(&self.squeue_args)
-                .arg("--array")
-                .arg("--noheader")
-                .arg("--Format")
-                .arg(&output_format)
-                .output()
-                .expect("failed to execute process")
-                .stdout
-                .lines()
-                .map(|l| l.unwrap().trim().to_string())
-                .filter_map(|l| {
-                    let parts: Vec<_> = l.split(output_separator).collect();
-
-                    if parts.len() != fields.len() + 1 {
-                        return None;
+            match query_squeue(&self.config, &self.parser, &mut self.unknown_ids) {
+                Ok((jobs, errors)) => {
+                    self.failures = 0;
+                    tracing::debug!(jobs = jobs.len(), "polled squeue");
+                    if !errors.is_empty() {
+                        tracing::warn!(
+                            skipped = errors.len(),
+                            first = %errors[0],
+                            "skipped unparsable squeue lines"
+                        );
+                        self.app.send(AppMessage::JobParseErrors(errors)).unwrap();
+                    }
+                    // the first poll sends the whole list, later ones what changed
+                    match &self.prev_jobs {
+                        None => self.app.send(AppMessage::Jobs(jobs.clone())).unwrap(),
+                        Some(prev_jobs) => {
+                            let changes = jobs_changed(prev_jobs, &jobs);
+                            self.notify_changes(&changes);
+                            self.app.send(AppMessage::JobsChanged(changes)).unwrap();
+                        }
+                    }
+                    self.prev_jobs = Some(jobs);
+                }
+                Err(e) => {
+                    let e = e.to_string();
+                    self.failures += 1;
+                    tracing::warn!(failures = self.failures, "squeue failed: {}", e);
+                    if self.failures >= self.config.max_retries {
+                        tracing::error!("giving up after {} failures", self.failures);
+                        self.app
+                            .send(AppMessage::WatcherDied(format!(
+                                "squeue failed {} times in a row: {}",
+                                self.failures, e
+                            )))
+                            .unwrap();
+                        return Ok(());
+                    }
+                    self.app.send(AppMessage::SqueueError(e)).unwrap();
+                }
+            }
+            select! {
+                recv(self.receiver) -> msg => {
+                    match msg? {
+                        WatcherCommand::PollNow => tracing::debug!("poll requested"),
+                        WatcherCommand::Shutdown => {
+                            tracing::info!("shutting down");
+                            return Ok(());
+                        }
                     }
+                }
+                default(self.config.backoff.delay(self.config.interval, self.failures)) => {}
+            }
+        }
+    }
 
-                    let id = parts[0];
-                    let name = parts[1];
-                    let state = parts[2];
-                    let user = parts[3];
-                    let time = parts[4];
-                    let tres = match parts[17] {
-                        "N/A" => format!("{}", parts[5]),
-                        _ => format!("{},{}", parts[5],parts[17]),
-                    };
-                    let partition = parts[6];
-                    let nodelist = parts[7];
-                    let stdout = parts[8];
-                    let stderr = parts[9];
-                    let command = parts[10];
-                    let state_compact = parts[11];
-                    let reason = parts[12];
-
-                    let array_job_id = parts[13];
-                    let array_task_id = parts[14];
-                    let node_list = parts[15];
-                    let working_dir = parts[16];
-
-                    Some(Job {
-                        job_id: id.to_owned(),
-                        array_id: array_job_id.to_owned(),
-                        array_step: match array_task_id {
-                            "N/A" => None,
-                            _ => Some(array_task_id.to_owned()),
-                        },
-                        name: name.to_owned(),
-                        state: state.to_owned(),
-                        state_compact: state_compact.to_owned(),
-                        reason: if reason == "None" {
-                            None
-                        } else {
-                            Some(reason.to_owned())
-                        },
-                        user: user.to_owned(),
-                        time: time.to_owned(),
-                        tres: tres.to_owned(),
-                        partition: partition.to_owned(),
-                        nodelist: nodelist.to_owned(),
-                        command: command.to_owned(),
-                        stdout: Self::resolve_path(
-                            stdout,
-                            array_job_id,
-                            array_task_id,
-                            id,
-                            node_list,
-                            user,
-                            name,
-                            working_dir,
-                        ),
-                        stderr: Self::resolve_path(
-                            stderr,
-                            array_job_id,
-                            array_task_id,
-                            id,
-                            node_list,
-                            user,
-                            name,
-                            working_dir,
-                        ), // TODO fill all fields
+    /// Sends the state changes, also of the jobs that left the queue.
+    fn notify_changes(&self, changes: &[JobChange]) {
+        let mut removed = Vec::new();
+        for change in changes {
+            match change {
+                JobChange::StateChanged { job, old_state } => self
+                    .app
+                    .send(AppMessage::JobStateChanged {
+                        id: job.id(),
+                        old_state: old_state.clone(),
+                        new_state: job.state.clone(),
                     })
-                })
-                .collect();
-            self.app.send(AppMessage::Jobs(jobs)).unwrap();
-            thread::sleep(self.interval);
+                    .unwrap(),
+                JobChange::Added(job) => tracing::debug!(id = job.id(), "job added"),
+                JobChange::Removed(job) => removed.push(job.clone()),
+                JobChange::Updated(_) => {}
+            }
         }
+        self.send_final_states(removed);
     }
 
-    fn resolve_path(
-        path: &str,
-        array_master: &str,
-        array_id: &str,
-        id: &str,
-        host: &str,
-        user: &str,
-        name: &str,
-        working_dir: &str,
-    ) -> Option<PathBuf> {
-        // see https://slurm.schedmd.com/sbatch.html#SECTION_%3CB%3Efilename-pattern%3C/B%3E
-        lazy_static::lazy_static! {
-            static ref RE: Regex = Regex::new(r"%(%|A|a|J|j|N|n|s|t|u|x)").unwrap();
+    /// Finished jobs leave the queue without squeue reporting their final
+    /// state, sacct knows it if it is on. It is asked on a thread of its own,
+    /// so that a slow sacct doesn't hold up the next poll.
+    fn send_final_states(&self, removed: Vec<Job>) {
+        if removed.is_empty() || self.config.sacct.is_none() {
+            return;
         }
+        let app = self.app.clone();
+        std::thread::spawn(move || {
+            let ids: Vec<_> = removed.iter().map(Job::id).collect();
+            let states: HashMap<_, _> = sacct_states(&ids.join(",")).into_iter().collect();
+            for job in removed {
+                let id = job.id();
+                match states.get(&id) {
+                    Some(state) if !JobState::new(state).is_active() => {
+                        app.send(AppMessage::JobStateChanged {
+                            id,
+                            old_state: job.state,
+                            new_state: state.clone(),
+                        })?
+                    }
+                    // e.g. removed from the watch file or past `max_jobs`
+                    Some(_) => tracing::debug!(id, "job left the list"),
+                    None => tracing::debug!(id, "job left the queue, sacct doesn't know it"),
+                }
+            }
+            Ok::<_, SendError<AppMessage>>(())
+        });
+    }
+}
 
-        let mut path = path.to_owned();
-        let slurm_no_val = "4294967294";
-        let array_id = if array_id == "N/A" {
-            slurm_no_val
-        } else {
-            array_id
-        };
+/// Runs squeue once. Lines that can't be parsed are reported on stderr.
+pub fn fetch_jobs(config: &JobWatcherConfig) -> Result<Vec<Job>, String> {
+    let (jobs, errors) = query_squeue(config, &SqueueParser::default(), &mut HashSet::new())
+        .map_err(|e| e.to_string())?;
+    for e in errors {
+        eprintln!("Skipped a line of squeue output: {}", e);
+    }
+    Ok(jobs)
+}
+
+#[derive(Debug)]
+enum SqueueError {
+    /// squeue could not be run or failed, possibly only this time, e.g. when
+    /// slurmctld doesn't respond.
+    Failed(String),
+    /// `--jobs` named a job squeue doesn't know (anymore). Depending on the
+    /// Slurm version this happens if any or all of the jobs finished too long
+    /// ago, so retrying doesn't help.
+    InvalidJobId,
+}
 
-        if path.is_empty() {
-            // never happens right now, because `squeue -O stdout` seems to always return something
-            path = if array_id == slurm_no_val {
-                PathBuf::from(working_dir).join("slurm-%J.out")
-            } else {
-                PathBuf::from(working_dir).join("slurm-%A_%a.out")
+impl fmt::Display for SqueueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SqueueError::Failed(e) => write!(f, "{}", e),
+            SqueueError::InvalidJobId => write!(f, "Invalid job id specified"),
+        }
+    }
+}
+
+/// With a `watch_file`, only the jobs listed in it are queried. The file is
+/// read on every poll, so jobs can be appended while turm is running. Listed
+/// jobs that already finished are not reported by squeue and thus left out,
+/// those squeue doesn't know anymore are added to `unknown_ids` and not
+/// queried again.
+fn query_squeue(
+    config: &JobWatcherConfig,
+    parser: &SqueueParser,
+    unknown_ids: &mut HashSet<String>,
+) -> Result<(Vec<Job>, Vec<JobParseError>), SqueueError> {
+    let (mut jobs, errors) = match watched_ids(config) {
+        Some(mut ids) => {
+            ids.retain(|id| !unknown_ids.contains(id));
+            match run_squeue(config, parser, Some(&ids)) {
+                // find out which of the jobs squeue doesn't know
+                Err(SqueueError::InvalidJobId) => {
+                    let mut jobs = Vec::new();
+                    let mut errors = Vec::new();
+                    for id in ids {
+                        match run_squeue(config, parser, Some(std::slice::from_ref(&id))) {
+                            Ok((j, e)) => {
+                                jobs.extend(j);
+                                errors.extend(e);
+                            }
+                            Err(SqueueError::InvalidJobId) => {
+                                tracing::info!(id, "squeue doesn't know the job anymore");
+                                unknown_ids.insert(id);
+                            }
+                            Err(e) => return Err(e),
+                        }
+                    }
+                    (jobs, errors)
+                }
+                result => result?,
             }
-            .to_str()
-            .unwrap()
-            .to_owned();
-        };
+        }
+        // e.g. `--jobs` in the squeue arguments
+        None => match run_squeue(config, parser, None) {
+            Err(SqueueError::InvalidJobId) => (Vec::new(), Vec::new()),
+            result => result?,
+        },
+    };
+    if let Some(max_jobs) = config.max_jobs {
+        jobs = most_recent(jobs, max_jobs);
+    }
+    Ok((jobs, errors))
+}
 
-        for cap in RE
-            .captures_iter(&path.clone())
-            .collect::<Vec<_>>() // TODO: this is stupid, there has to be a better way to reverse the captures...
-            .iter()
-            .rev()
-        {
-            let m = cap.get(0).unwrap();
-            let replacement = match m.as_str() {
-                "%%" => "%",
-                "%A" => array_master,
-                "%a" => array_id,
-                "%J" => id,
-                "%j" => id,
-                "%N" => host.split(',').next().unwrap_or(host),
-                "%n" => "0",
-                "%s" => "batch",
-                "%t" => "0",
-                "%u" => user,
-                "%x" => name,
-                _ => unreachable!(),
-            };
-
-            path.replace_range(m.range(), replacement);
+/// Queries `ids` only if given, nothing if they are empty.
+fn run_squeue(
+    config: &JobWatcherConfig,
+    parser: &SqueueParser,
+    ids: Option<&[String]>,
+) -> Result<(Vec<Job>, Vec<JobParseError>), SqueueError> {
+    if ids.is_some_and(|ids| ids.is_empty()) {
+        return Ok((Vec::new(), Vec::new()));
+    }
+    let output = squeue_command(config, parser, ids).output().map_err(|e| {
+        SqueueError::Failed(format!(
+            "Failed to execute {}: {}",
+            config.squeue_binary.display(),
+            e
+        ))
+    })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(match stderr.trim() {
+            "" => SqueueError::Failed(format!("squeue failed: {}", output.status)),
+            // `slurm_load_jobs error: Invalid job id specified`
+            stderr if stderr.contains("Invalid job id specified") => SqueueError::InvalidJobId,
+            // e.g. `squeue: error: Invalid user: nobody`
+            stderr => SqueueError::Failed(stderr.to_owned()),
+        });
+    }
+
+    let mut jobs = Vec::new();
+    let mut errors = Vec::new();
+    for result in parser.parse(&output.stdout) {
+        match result {
+            Ok(job) => jobs.push(job),
+            Err(e) => errors.push(e),
         }
+    }
+    Ok((jobs, errors))
+}
+
+/// The `n` most recently submitted jobs, in their original order. squeue
+/// can't do this itself, its `--sort` would change the order of the list.
+fn most_recent(mut jobs: Vec<Job>, n: usize) -> Vec<Job> {
+    let mut newest_first: Vec<usize> = (0..jobs.len()).collect();
+    // stable, so of jobs submitted at the same time the first ones are kept,
+    // jobs without a submit time count as the oldest
+    newest_first.sort_by(|&a, &b| jobs[b].submit_time.cmp(&jobs[a].submit_time));
+    let mut keep = vec![false; jobs.len()];
+    for i in newest_first.into_iter().take(n) {
+        keep[i] = true;
+    }
+    let mut keep = keep.into_iter();
+    jobs.retain(|_| keep.next().unwrap_or(false));
+    jobs
+}
 
-        Some(PathBuf::from(working_dir).join(path)) // works even if `path` is absolute
+/// The jobs listed in the `watch_file`, `None` without one.
+fn watched_ids(config: &JobWatcherConfig) -> Option<Vec<String>> {
+    let path = config.watch_file.as_ref()?;
+    // the file may not have been written yet
+    Some(
+        fs::read_to_string(path)
+            .map(|s| parse_job_ids(&s))
+            .unwrap_or_default(),
+    )
+}
+
+fn squeue_command(
+    config: &JobWatcherConfig,
+    parser: &SqueueParser,
+    ids: Option<&[String]>,
+) -> Command {
+    let mut squeue_args = config.squeue_args.clone();
+    if let Some(ids) = ids {
+        squeue_args.push(format!("--jobs={}", ids.join(",")));
+    }
+
+    let mut command = Command::new(&config.squeue_binary);
+    command
+        .args(squeue_args)
+        .arg("--array")
+        .arg("--noheader")
+        .arg("--Format")
+        .arg(parser.format());
+    command
+}
+
+/// The squeue command line as it would be run, quoted for the shell. `None`
+/// if the `watch_file` lists no jobs, squeue isn't run then.
+pub fn squeue_command_line(config: &JobWatcherConfig) -> Option<String> {
+    let ids = watched_ids(config);
+    if ids.as_ref().is_some_and(|ids| ids.is_empty()) {
+        return None;
     }
+    let command = squeue_command(config, &SqueueParser::default(), ids.as_deref());
+    let words: Vec<_> = once(command.get_program())
+        .chain(command.get_args())
+        .map(|w| shell_quote(&w.to_string_lossy()))
+        .collect();
+    Some(words.join(" "))
+}
+
+fn shell_quote(word: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "-_./=:,@%+".contains(c);
+    if !word.is_empty() && word.chars().all(is_safe) {
+        word.to_owned()
+    } else {
+        format!("'{}'", word.replace('\'', "'\\''"))
+    }
+}
+
+/// Reads one job ID per line, either bare (`1234`, `1234_5`, `1234_[1-5]`)
+/// or as printed by sbatch (`Submitted batch job 1234`). Other lines are
+/// ignored.
+fn parse_job_ids(s: &str) -> Vec<String> {
+    s.lines()
+        .filter_map(|l| l.split_whitespace().last())
+        .filter_map(|id| parse_job_id(id).ok())
+        .collect()
 }
 
 impl JobWatcherHandle {
-    pub fn new(app: Sender<AppMessage>, interval: Duration, squeue_args: Vec<String>) -> Self {
-        let mut actor = JobWatcher::new(app, interval, squeue_args);
-        thread::spawn(move || actor.run());
+    pub fn new(app: Sender<AppMessage>, config: JobWatcherConfig) -> Self {
+        let (sender, receiver) = unbounded();
+        let mut actor = JobWatcher::new(app, receiver, config);
+        let thread = thread::spawn(move || actor.run());
+
+        Self {
+            sender,
+            thread: Some(thread),
+        }
+    }
+
+    /// Stops the watcher and waits for it, which takes until a running
+    /// squeue returns.
+    pub fn shutdown(&mut self) {
+        let _ = self.sender.send(WatcherCommand::Shutdown);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    pub fn poll_now(&self) {
+        // the watcher may have given up
+        let _ = self.sender.send(WatcherCommand::PollNow);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job::{output_separator, test_squeue_line};
+
+    #[test]
+    fn test_parse_job_ids() {
+        let file = "Submitted batch job 1234
+1235
+  1236_7\t
+1237_[1-5]
+
+sbatch: error: Batch job submission failed
+# comment
+";
+        assert_eq!(
+            parse_job_ids(file),
+            ["1234", "1235", "1236_7", "1237_[1-5]"]
+        );
+    }
+
+    #[test]
+    fn test_backoff() {
+        let backoff = Backoff::default();
+        let interval = Duration::from_secs(2);
+        let delays: Vec<_> = (0..7)
+            .map(|n| backoff.delay(interval, n).as_secs())
+            .collect();
+        assert_eq!(delays, [2, 2, 4, 8, 16, 32, 60]);
+        assert_eq!(backoff.delay(interval, 100).as_secs(), 60);
+
+        // the interval wins over a smaller maximum
+        let backoff = Backoff {
+            max_delay: Duration::from_secs(1),
+        };
+        assert_eq!(backoff.delay(interval, 3).as_secs(), 2);
+    }
+
+    #[test]
+    fn test_config() {
+        let config = JobWatcherConfig::default();
+        assert_eq!(config.max_retries, 10);
+        assert_eq!(config.sacct_since(), None);
+        assert!(config.job_filter().is_empty());
+
+        let config = config
+            .max_retries(3)
+            .sacct_enabled(Some(Duration::from_secs(3600)))
+            .filter(JobFilter {
+                user: Some("alice".to_owned()),
+                ..Default::default()
+            });
+        assert_eq!(config.max_retries, 3);
+        assert_eq!(config.sacct_since(), Some(Duration::from_secs(3600)));
+        assert_eq!(config.job_filter().user.as_deref(), Some("alice"));
+    }
 
-        Self {}
+    #[test]
+    fn test_shutdown() {
+        // an empty watch file never runs squeue
+        let (app, jobs) = unbounded();
+        let config = JobWatcherConfig::default()
+            .interval(Duration::from_secs(3600))
+            .watch_file(Some(PathBuf::from("/nonexistent/turm-jobs")));
+        let mut watcher = JobWatcherHandle::new(app, config);
+        assert!(matches!(jobs.recv(), Ok(AppMessage::Jobs(j)) if j.is_empty()));
+
+        watcher.shutdown();
+        assert!(watcher.thread.is_none());
+        // the watcher dropped its sender
+        assert!(jobs.recv().is_err());
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("--user=alice"), "--user=alice");
+        assert_eq!(shell_quote("jobid:###,name:###"), "'jobid:###,name:###'");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+        assert_eq!(shell_quote(""), "''");
+    }
+
+    #[test]
+    fn test_most_recent() {
+        let separator = output_separator();
+        let job = |id: &str, submit_time: &str| {
+            let line = test_squeue_line(&separator, &[("jobid", id), ("submittime", submit_time)]);
+            Job::from_parts(&line, &separator).unwrap()
+        };
+
+        let jobs = vec![
+            job("1", "2024-05-01T10:00:00"),
+            job("2", "2024-05-01T12:00:00"),
+            job("3", "N/A"),
+            job("4", "2024-05-01T11:00:00"),
+            job("5", "2024-05-01T11:00:00"),
+        ];
+        let ids = |jobs: Vec<Job>| jobs.iter().map(Job::id).collect::<Vec<_>>();
+        assert_eq!(ids(most_recent(jobs.clone(), 2)), ["2", "4"]);
+        assert_eq!(ids(most_recent(jobs.clone(), 4)), ["1", "2", "4", "5"]);
+        assert_eq!(ids(most_recent(jobs.clone(), 10)).len(), 5);
+        assert!(most_recent(jobs, 0).is_empty());
     }
 }
@@ -0,0 +1,336 @@
+use crossterm::event::KeyCode;
+use serde::Deserialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    Up,
+    Down,
+    NextPage,
+    PreviousPage,
+    FocusPrevious,
+    FocusNext,
+    ToggleLog,
+    CancelJob,
+    Details,
+    HoldJob,
+    ReleaseJob,
+    SwitchOutput,
+    Wrap,
+    Sort,
+    Columns,
+    Filter,
+    MoveColumnUp,
+    MoveColumnDown,
+    ToggleColumn,
+    JobsTab,
+    UsersTab,
+    PartitionsTab,
+    DependenciesTab,
+    AccountsTab,
+    Ssh,
+    Attach,
+    Refresh,
+    CopyId,
+    Export,
+    WiderName,
+    NarrowerName,
+    Dependency,
+    Help,
+}
+
+impl Action {
+    /// For the help popup.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Up => "previous job, scroll up",
+            Action::Down => "next job, scroll down",
+            Action::NextPage => "a page of jobs down",
+            Action::PreviousPage => "a page of jobs up",
+            Action::FocusPrevious => "focus the job list",
+            Action::FocusNext => "focus the node list",
+            Action::ToggleLog => "show or hide the log",
+            Action::CancelJob => "cancel the job",
+            Action::Details => "show all details of the job",
+            Action::HoldJob => "hold the pending job",
+            Action::ReleaseJob => "release the held job",
+            Action::SwitchOutput => "switch between stdout and stderr",
+            Action::Wrap => "wrap long log lines",
+            Action::Sort => "sort by a column",
+            Action::Columns => "choose the columns",
+            Action::Filter => "filter jobs by name",
+            Action::MoveColumnUp => "move the column up, in the column picker",
+            Action::MoveColumnDown => "move the column down, in the column picker",
+            Action::ToggleColumn => "show or hide the column, in the column picker",
+            Action::JobsTab => "jobs tab",
+            Action::UsersTab => "users tab",
+            Action::PartitionsTab => "partitions tab",
+            Action::DependenciesTab => "dependencies tab",
+            Action::AccountsTab => "accounts tab",
+            Action::Ssh => "ssh to the first node of the job",
+            Action::Attach => "attach to the job with sattach",
+            Action::Refresh => "refresh the job list now",
+            Action::CopyId => "copy the job id",
+            Action::Export => "export the job list to ~/turm-snapshot-<time>.json",
+            Action::WiderName => "wider job names",
+            Action::NarrowerName => "narrower job names",
+            Action::Dependency => "select the job the job depends on",
+            Action::Help => "show this help",
+        }
+    }
+}
+
+/// The keys bound to an action. In the config file either a single key or a
+/// list of keys, e.g. `"q"` or `["k", "up"]`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "KeyList")]
+pub struct Keys(Vec<KeyCode>);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl TryFrom<KeyList> for Keys {
+    type Error = String;
+
+    fn try_from(keys: KeyList) -> Result<Self, Self::Error> {
+        let keys = match keys {
+            KeyList::One(key) => vec![key],
+            KeyList::Many(keys) => keys,
+        };
+        keys.iter()
+            .map(|k| parse_key(k))
+            .collect::<Result<_, _>>()
+            .map(Keys)
+    }
+}
+
+impl Keys {
+    fn new(keys: &[KeyCode]) -> Self {
+        Self(keys.to_vec())
+    }
+
+    pub fn matches(&self, code: KeyCode) -> bool {
+        self.0.contains(&code)
+    }
+
+    /// For the help line, e.g. `o/tab`.
+    pub fn label(&self) -> String {
+        self.0.iter().map(key_name).collect::<Vec<_>>().join("/")
+    }
+}
+
+/// The `[keybindings]` section of the config file.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Keybindings {
+    pub quit: Keys,
+    pub up: Keys,
+    pub down: Keys,
+    pub next_page: Keys,
+    pub previous_page: Keys,
+    pub focus_previous: Keys,
+    pub focus_next: Keys,
+    pub toggle_log: Keys,
+    pub cancel_job: Keys,
+    pub details: Keys,
+    pub hold_job: Keys,
+    pub release_job: Keys,
+    pub switch_output: Keys,
+    pub wrap: Keys,
+    pub sort: Keys,
+    pub columns: Keys,
+    pub filter: Keys,
+    pub move_column_up: Keys,
+    pub move_column_down: Keys,
+    pub toggle_column: Keys,
+    pub jobs_tab: Keys,
+    pub users_tab: Keys,
+    pub partitions_tab: Keys,
+    pub dependencies_tab: Keys,
+    pub accounts_tab: Keys,
+    pub ssh: Keys,
+    pub attach: Keys,
+    pub refresh: Keys,
+    pub copy_id: Keys,
+    pub export: Keys,
+    pub wider_name: Keys,
+    pub narrower_name: Keys,
+    pub dependency: Keys,
+    pub help: Keys,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Self {
+            quit: Keys::new(&[KeyCode::Char('q')]),
+            up: Keys::new(&[KeyCode::Char('k'), KeyCode::Up]),
+            down: Keys::new(&[KeyCode::Char('j'), KeyCode::Down]),
+            next_page: Keys::new(&[KeyCode::Char('f')]),
+            previous_page: Keys::new(&[KeyCode::Char('b')]),
+            focus_previous: Keys::new(&[KeyCode::Char('h'), KeyCode::Left]),
+            focus_next: Keys::new(&[KeyCode::Char('l'), KeyCode::Right]),
+            toggle_log: Keys::new(&[KeyCode::Enter]),
+            cancel_job: Keys::new(&[KeyCode::Char('c')]),
+            details: Keys::new(&[KeyCode::Char('d')]),
+            hold_job: Keys::new(&[KeyCode::Char('H')]),
+            release_job: Keys::new(&[KeyCode::Char('R')]),
+            switch_output: Keys::new(&[KeyCode::Char('o'), KeyCode::Tab]),
+            wrap: Keys::new(&[KeyCode::Char('w')]),
+            sort: Keys::new(&[KeyCode::Char('s')]),
+            columns: Keys::new(&[KeyCode::Char('C')]),
+            filter: Keys::new(&[KeyCode::Char('/')]),
+            move_column_up: Keys::new(&[KeyCode::Char('K')]),
+            move_column_down: Keys::new(&[KeyCode::Char('J')]),
+            toggle_column: Keys::new(&[KeyCode::Char(' ')]),
+            jobs_tab: Keys::new(&[KeyCode::Char('1')]),
+            users_tab: Keys::new(&[KeyCode::Char('2')]),
+            partitions_tab: Keys::new(&[KeyCode::Char('3')]),
+            dependencies_tab: Keys::new(&[KeyCode::Char('4')]),
+            accounts_tab: Keys::new(&[KeyCode::Char('5')]),
+            ssh: Keys::new(&[KeyCode::Char('S')]),
+            attach: Keys::new(&[KeyCode::Char('a')]),
+            refresh: Keys::new(&[KeyCode::Char('r')]),
+            copy_id: Keys::new(&[KeyCode::Char('y')]),
+            export: Keys::new(&[KeyCode::Char('e')]),
+            wider_name: Keys::new(&[KeyCode::Char('+')]),
+            narrower_name: Keys::new(&[KeyCode::Char('-')]),
+            dependency: Keys::new(&[KeyCode::Char('D')]),
+            help: Keys::new(&[KeyCode::Char('?')]),
+        }
+    }
+}
+
+impl Keybindings {
+    /// Every action with its keys, in the order of the fields.
+    pub fn bindings(&self) -> [(Action, &Keys); 34] {
+        [
+            (Action::Quit, &self.quit),
+            (Action::Up, &self.up),
+            (Action::Down, &self.down),
+            (Action::NextPage, &self.next_page),
+            (Action::PreviousPage, &self.previous_page),
+            (Action::FocusPrevious, &self.focus_previous),
+            (Action::FocusNext, &self.focus_next),
+            (Action::ToggleLog, &self.toggle_log),
+            (Action::CancelJob, &self.cancel_job),
+            (Action::Details, &self.details),
+            (Action::HoldJob, &self.hold_job),
+            (Action::ReleaseJob, &self.release_job),
+            (Action::SwitchOutput, &self.switch_output),
+            (Action::Wrap, &self.wrap),
+            (Action::Sort, &self.sort),
+            (Action::Columns, &self.columns),
+            (Action::Filter, &self.filter),
+            (Action::MoveColumnUp, &self.move_column_up),
+            (Action::MoveColumnDown, &self.move_column_down),
+            (Action::ToggleColumn, &self.toggle_column),
+            (Action::JobsTab, &self.jobs_tab),
+            (Action::UsersTab, &self.users_tab),
+            (Action::PartitionsTab, &self.partitions_tab),
+            (Action::DependenciesTab, &self.dependencies_tab),
+            (Action::AccountsTab, &self.accounts_tab),
+            (Action::Ssh, &self.ssh),
+            (Action::Attach, &self.attach),
+            (Action::Refresh, &self.refresh),
+            (Action::CopyId, &self.copy_id),
+            (Action::Export, &self.export),
+            (Action::WiderName, &self.wider_name),
+            (Action::NarrowerName, &self.narrower_name),
+            (Action::Dependency, &self.dependency),
+            (Action::Help, &self.help),
+        ]
+    }
+
+    /// The first action bound to `code`, in the order of the fields.
+    pub fn action(&self, code: KeyCode) -> Option<Action> {
+        self.bindings()
+            .into_iter()
+            .find(|(_, keys)| keys.matches(code))
+            .map(|(action, _)| action)
+    }
+}
+
+const NAMED_KEYS: [(&str, KeyCode); 14] = [
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("space", KeyCode::Char(' ')),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+];
+
+/// A single character, a key name like `enter` or `space`, or `f1` to `f12`.
+fn parse_key(s: &str) -> Result<KeyCode, String> {
+    let mut chars = s.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(KeyCode::Char(c));
+    }
+    let lower = s.to_ascii_lowercase();
+    if let Some((_, code)) = NAMED_KEYS.iter().find(|(name, _)| *name == lower) {
+        return Ok(*code);
+    }
+    match lower.strip_prefix('f').and_then(|n| n.parse().ok()) {
+        Some(n @ 1..=12) => Ok(KeyCode::F(n)),
+        _ => Err(format!("unknown key `{}`", s)),
+    }
+}
+
+fn key_name(code: &KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "space".to_owned(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("f{}", n),
+        code => NAMED_KEYS
+            .iter()
+            .find(|(_, c)| c == code)
+            .map(|(name, _)| name.to_string())
+            .unwrap_or_else(|| format!("{:?}", code).to_lowercase()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(parse_key("q"), Ok(KeyCode::Char('q')));
+        assert_eq!(parse_key("Q"), Ok(KeyCode::Char('Q')));
+        assert_eq!(parse_key("Enter"), Ok(KeyCode::Enter));
+        assert_eq!(parse_key("space"), Ok(KeyCode::Char(' ')));
+        assert_eq!(parse_key("f5"), Ok(KeyCode::F(5)));
+        assert!(parse_key("f13").is_err());
+        assert!(parse_key("ctrl-c").is_err());
+
+        for (name, code) in NAMED_KEYS {
+            assert_eq!(key_name(&code), name);
+        }
+    }
+
+    #[test]
+    fn test_keybindings() {
+        let keybindings = Keybindings::default();
+        assert_eq!(keybindings.action(KeyCode::Up), Some(Action::Up));
+        assert_eq!(keybindings.action(KeyCode::Tab), Some(Action::SwitchOutput));
+        assert_eq!(keybindings.action(KeyCode::Char('?')), Some(Action::Help));
+        assert_eq!(
+            keybindings.action(KeyCode::Char('f')),
+            Some(Action::NextPage)
+        );
+        assert_eq!(keybindings.action(KeyCode::Char('x')), None);
+        assert_eq!(keybindings.switch_output.label(), "o/tab");
+    }
+}
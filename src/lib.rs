@@ -0,0 +1,28 @@
+pub mod aggregate;
+pub mod app;
+pub mod cluster;
+pub mod columns;
+pub mod commands;
+pub mod config;
+pub mod dependency;
+pub mod diff;
+pub mod file_watcher;
+pub mod filter;
+pub mod job;
+pub mod job_detail;
+pub mod job_watcher;
+pub mod keybindings;
+pub mod list;
+pub mod logging;
+pub mod mock;
+pub mod nodelist;
+pub mod partition_watcher;
+pub mod reason;
+pub mod replay;
+pub mod sacct_watcher;
+pub mod sort;
+pub mod squeue_args;
+pub mod squeue_parser;
+pub mod styles;
+pub mod theme;
+pub mod tres;
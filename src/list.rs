@@ -0,0 +1,161 @@
+use crate::job::Job;
+use crate::sort::SortKey;
+
+/// The columns of `turm list`.
+pub const DEFAULT_FIELDS: [SortKey; 7] = [
+    SortKey::JobId,
+    SortKey::Partition,
+    SortKey::Name,
+    SortKey::User,
+    SortKey::State,
+    SortKey::Elapsed,
+    SortKey::TimeLimit,
+];
+
+/// The name of the field for `--columns` and in CSV headers.
+fn field_name(field: SortKey) -> &'static str {
+    match field {
+        SortKey::JobId => "job_id",
+        SortKey::Name => "name",
+        SortKey::State => "state",
+        SortKey::User => "user",
+        SortKey::Elapsed => "elapsed",
+        SortKey::TimeLimit => "time_limit",
+        SortKey::Priority => "priority",
+        SortKey::Partition => "partition",
+        SortKey::Gpu => "gpus",
+        SortKey::Cpu => "cpus",
+    }
+}
+
+/// Parses one of the comma separated names of `--columns`.
+pub fn parse_field(s: &str) -> Result<SortKey, String> {
+    SortKey::ALL
+        .into_iter()
+        .find(|&f| field_name(f) == s)
+        .ok_or_else(|| {
+            let names: Vec<_> = SortKey::ALL.into_iter().map(field_name).collect();
+            format!("unknown column `{}`, available are {}", s, names.join(", "))
+        })
+}
+
+fn cell(job: &Job, field: SortKey) -> String {
+    let number = |n: Option<u32>| n.map(|n| n.to_string()).unwrap_or_default();
+    match field {
+        SortKey::JobId => job.id(),
+        SortKey::Name => job.name.clone(),
+        SortKey::State => job.state.clone(),
+        SortKey::User => job.user.clone(),
+        SortKey::Elapsed => job.time.clone(),
+        SortKey::TimeLimit => job.time_limit.clone().unwrap_or_default(),
+        SortKey::Priority => number(job.priority),
+        SortKey::Partition => job.partition.clone(),
+        SortKey::Gpu => number(job.gpu_count()),
+        SortKey::Cpu => number(job.tres_parsed().map(|t| t.cpu)),
+    }
+}
+
+fn rows(jobs: &[&Job], fields: &[SortKey]) -> Vec<Vec<String>> {
+    jobs.iter()
+        .map(|j| fields.iter().map(|&f| cell(j, f)).collect())
+        .collect()
+}
+
+/// A table like squeue prints it, with upper case headers.
+pub fn text(jobs: &[&Job], fields: &[SortKey]) -> String {
+    let header: Vec<String> = fields.iter().map(|f| f.name().to_uppercase()).collect();
+    text_table(&header, &rows(jobs, fields))
+}
+
+/// RFC 4180 CSV with a header row.
+pub fn csv(jobs: &[&Job], fields: &[SortKey]) -> String {
+    let header: Vec<String> = fields.iter().map(|&f| field_name(f).to_owned()).collect();
+    csv_table(&header, &rows(jobs, fields))
+}
+
+fn text_table(header: &[String], rows: &[Vec<String>]) -> String {
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            rows.iter()
+                .map(|r| r[i].chars().count())
+                .chain([header[i].chars().count()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let mut out = String::new();
+    for row in [header].into_iter().chain(rows.iter().map(Vec::as_slice)) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        out.push_str(line.join("  ").trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+fn csv_table(header: &[String], rows: &[Vec<String>]) -> String {
+    let mut out = String::new();
+    for row in [header].into_iter().chain(rows.iter().map(Vec::as_slice)) {
+        let fields: Vec<String> = row.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&fields.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> (Vec<String>, Vec<Vec<String>>) {
+        let strings = |s: &[&str]| s.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        (
+            strings(&["ID", "NAME", "STATE"]),
+            vec![
+                strings(&["1234", "train, large", "RUNNING"]),
+                strings(&["1235_10", "say \"hi\"", "PENDING"]),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_parse_field() {
+        for field in SortKey::ALL {
+            assert_eq!(parse_field(field_name(field)), Ok(field));
+        }
+        assert_eq!(parse_field("gpus"), Ok(SortKey::Gpu));
+        let e = parse_field("size").unwrap_err();
+        assert!(e.starts_with("unknown column `size`, available are job_id, name,"));
+    }
+
+    #[test]
+    fn test_text_table() {
+        let (header, rows) = table();
+        assert_eq!(
+            text_table(&header, &rows),
+            "ID       NAME          STATE\n\
+             1234     train, large  RUNNING\n\
+             1235_10  say \"hi\"      PENDING\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_table() {
+        let (header, rows) = table();
+        assert_eq!(
+            csv_table(&header, &rows),
+            "ID,NAME,STATE\r\n1234,\"train, large\",RUNNING\r\n1235_10,\"say \"\"hi\"\"\",PENDING\r\n"
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io::{self, IsTerminal},
+    os::unix::fs::OpenOptionsExt,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+
+/// Where the TUI logs to without `--log-file`, it owns the terminal:
+/// `$XDG_STATE_HOME/turm/turm.log`, or `~/.local/state/turm/turm.log`. Not
+/// in `/tmp`, which other users of a login node can read.
+pub fn default_log_file() -> Option<PathBuf> {
+    env::var_os("XDG_STATE_HOME")
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|h| PathBuf::from(h).join(".local/state")))
+        .map(|d| d.join("turm").join("turm.log"))
+}
+
+/// Installs the global subscriber. Logs go to `file` if given, else to
+/// stderr, or to [`default_log_file`] in the TUI. Only an explicit `file`
+/// that can't be opened is an error, the TUI then just doesn't log.
+pub fn init(level: LevelFilter, file: Option<&Path>, tui: bool) -> io::Result<()> {
+    if level == LevelFilter::OFF {
+        return Ok(());
+    }
+    let writer = match (file, tui) {
+        (Some(path), _) => BoxMakeWriter::new(Mutex::new(open(path)?)),
+        (None, true) => {
+            let file = default_log_file().and_then(|path| {
+                fs::create_dir_all(path.parent()?).ok()?;
+                open(&path).ok()
+            });
+            match file {
+                Some(file) => BoxMakeWriter::new(Mutex::new(file)),
+                // e.g. no `$HOME`
+                None => return Ok(()),
+            }
+        }
+        (None, false) => BoxMakeWriter::new(io::stderr),
+    };
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_ansi(file.is_none() && !tui && io::stderr().is_terminal())
+        .with_writer(writer)
+        .init();
+    Ok(())
+}
+
+/// Only readable by the user, the log contains squeue arguments and job IDs.
+fn open(path: &Path) -> io::Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .mode(0o600)
+        .open(path)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+}
@@ -1,12 +1,9 @@
-mod app;
-mod file_watcher;
-mod job_watcher;
-mod squeue_args;
-
-use app::App;
+use clap::builder::FalseyValueParser;
 use clap::CommandFactory;
 use clap::Parser;
 use clap::Subcommand;
+use clap::ValueEnum;
+use clap::{ArgMatches, FromArgMatches};
 use clap_complete::{generate, Shell};
 use crossbeam::channel::{unbounded, Sender};
 use crossterm::{
@@ -18,19 +15,186 @@ use ratatui::{
     backend::{Backend, CrosstermBackend},
     Terminal,
 };
-use squeue_args::SqueueArgs;
-use std::{io, thread};
+use regex::Regex;
+use std::{
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+use tracing::level_filters::LevelFilter;
+use turm::app::{App, AppConfig, JobFeed, INPUT_POLL_INTERVAL};
+use turm::columns::Column;
+use turm::config::Config;
+use turm::filter::JobFilter;
+use turm::job_watcher::{self, Backoff, JobWatcherConfig};
+use turm::sort::SortKey;
+use turm::squeue_args::{self, SqueueArgs};
+use turm::theme::ColorDepth;
+use turm::{cluster, commands, diff, job, list, logging, replay, sacct_watcher};
 
 #[derive(Parser)]
-#[command(author, version, about, long_about = None)]
+#[command(
+    author,
+    version,
+    // replaced by our own, which includes the Slurm version
+    disable_version_flag = true,
+    about,
+    long_about = None,
+    after_help = "Flags can also be set with the TURM_* environment variables shown above, e.g. \
+                  TURM_INTERVAL=10. Flags take precedence over environment variables, which take \
+                  precedence over the config file."
+)]
 struct Cli {
-    /// Refresh rate for the job watcher.
-    #[arg(long, value_name = "SECONDS", default_value_t = 2)]
-    slurm_refresh: u64,
+    /// Refresh rate for the job watcher [default: 2].
+    #[arg(
+        short = 'i',
+        long,
+        visible_alias = "interval",
+        value_name = "SECONDS",
+        value_parser = clap::value_parser!(u64).range(1..),
+        env = "TURM_INTERVAL"
+    )]
+    slurm_refresh: Option<u64>,
+
+    /// Refresh rate for the file watcher [default: 2].
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        value_parser = clap::value_parser!(u64).range(1..),
+        env = "TURM_FILE_REFRESH"
+    )]
+    file_refresh: Option<u64>,
+
+    /// Longest wait between squeue retries after a failure, the wait doubles
+    /// with every failure [default: 60].
+    #[arg(long, value_name = "SECONDS", env = "TURM_MAX_BACKOFF")]
+    max_backoff: Option<u64>,
+
+    /// Stop polling squeue after this many failures in a row [default: 10].
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..), env = "TURM_MAX_RETRIES")]
+    max_retries: Option<u32>,
+
+    /// Only show the N most recently submitted jobs. squeue still lists all
+    /// jobs, turm drops the older ones, so on a busy cluster some jobs of
+    /// interest may be missing.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..), env = "TURM_MAX_JOBS")]
+    max_jobs: Option<u64>,
+
+    /// Print the jobs in FORMAT and exit instead of starting the TUI.
+    #[arg(short, long, value_name = "FORMAT", env = "TURM_OUTPUT")]
+    output: Option<OutputFormat>,
+
+    /// Only show the jobs listed in PATH, one ID per line. sbatch output
+    /// (`Submitted batch job <ID>`) works as well.
+    #[arg(long, value_name = "PATH", env = "TURM_WATCH_FILE")]
+    watch_file: Option<PathBuf>,
+
+    /// Also show finished jobs from sacct.
+    #[arg(long, overrides_with = "no_sacct", value_parser = FalseyValueParser::new(), env = "TURM_SACCT")]
+    sacct: bool,
+
+    /// Do not show finished jobs from sacct, even if the config file enables
+    /// it.
+    #[arg(long, overrides_with = "sacct")]
+    no_sacct: bool,
+
+    /// How far back sacct looks for finished jobs, e.g. `12h` or `7d`, at
+    /// most 30 days.
+    #[arg(long, value_name = "DURATION", default_value = "24h", value_parser = sacct_watcher::parse_since, env = "TURM_SINCE")]
+    since: Duration,
+
+    /// Send a desktop notification when a job enters STATE. Can be given
+    /// multiple times or comma separated [default: COMPLETED FAILED TIMEOUT].
+    /// With --sacct, also the final state of jobs that left the queue.
+    #[arg(
+        long,
+        value_name = "STATE",
+        value_delimiter = ',',
+        env = "TURM_NOTIFY_ON"
+    )]
+    notify_on: Vec<String>,
+
+    /// Print the squeue command that would be run and exit.
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Print the version of turm and of Slurm and exit.
+    #[arg(short = 'V', long)]
+    version: bool,
+
+    /// Write the default config file to ~/.config/turm/config.toml and exit.
+    #[arg(long)]
+    init_config: bool,
+
+    /// Only show jobs whose name matches REGEX.
+    #[arg(long, value_name = "REGEX", env = "TURM_FILTER_NAME")]
+    filter_name: Option<Regex>,
+
+    /// The squeue program to run, e.g. a site specific wrapper [default:
+    /// squeue].
+    #[arg(long, value_name = "PATH", value_parser = commands::parse_executable, env = "TURM_SQUEUE_BINARY")]
+    squeue_binary: Option<PathBuf>,
+
+    /// The scontrol program to run [default: scontrol].
+    #[arg(long, value_name = "PATH", value_parser = commands::parse_executable, env = "TURM_SCONTROL_BINARY")]
+    scontrol_binary: Option<PathBuf>,
+
+    /// The scancel program to run [default: scancel].
+    #[arg(long, value_name = "PATH", value_parser = commands::parse_executable, env = "TURM_SCANCEL_BINARY")]
+    scancel_binary: Option<PathBuf>,
+
+    /// Log messages up to LEVEL: off, error, warn, info, debug or trace.
+    #[arg(
+        long,
+        value_name = "LEVEL",
+        default_value = "warn",
+        env = "TURM_LOG_LEVEL"
+    )]
+    log_level: LevelFilter,
+
+    /// Append the log to PATH instead of stderr. The TUI logs to
+    /// `$XDG_STATE_HOME/turm/turm.log` (`~/.local/state/turm/turm.log`) by
+    /// default.
+    #[arg(long, value_name = "PATH", env = "TURM_LOG_FILE")]
+    log_file: Option<PathBuf>,
+
+    /// Only show jobs in STATE, e.g. `RUNNING` or `R`.
+    #[arg(long, value_name = "STATE", env = "TURM_FILTER_STATE")]
+    filter_state: Option<String>,
+
+    /// Only show jobs with one of the comma separated QOS, e.g. `normal,debug`.
+    #[arg(long, value_name = "QOS", env = "TURM_FILTER_QOS")]
+    filter_qos: Option<String>,
 
-    /// Refresh rate for the file watcher.
-    #[arg(long, value_name = "SECONDS", default_value_t = 2)]
-    file_refresh: u64,
+    /// Only show jobs charged to one of the comma separated ACCOUNTS.
+    #[arg(long, value_name = "ACCOUNTS", env = "TURM_FILTER_ACCOUNT")]
+    filter_account: Option<String>,
+
+    /// Only show jobs running in the reservation NAME.
+    #[arg(long, value_name = "NAME", env = "TURM_FILTER_RESERVATION")]
+    filter_reservation: Option<String>,
+
+    /// Only show job counts, partition usage and the busiest users instead of
+    /// the job list.
+    #[arg(long, value_parser = FalseyValueParser::new(), env = "TURM_OVERVIEW")]
+    overview: bool,
+
+    /// Print the job list on every refresh instead of starting the TUI, for
+    /// terminals without raw mode like Jupyter notebooks.
+    #[arg(long, value_parser = FalseyValueParser::new(), env = "TURM_NO_TUI")]
+    no_tui: bool,
+
+    /// Show the jobs of FILE, written by `--output json`, instead of running
+    /// squeue, e.g. to work on turm without a Slurm cluster. FILE is read on
+    /// every refresh.
+    #[arg(long, value_name = "FILE", env = "TURM_MOCK")]
+    mock: Option<PathBuf>,
 
     /// squeue arguments
     #[command(flatten)]
@@ -40,6 +204,13 @@ struct Cli {
     command: Option<CliCommand>,
 }
 
+#[derive(Clone, ValueEnum)]
+enum OutputFormat {
+    Json,
+    Text,
+    Csv,
+}
+
 #[derive(Subcommand)]
 enum CliCommand {
     /// Print shell completion script to stdout.
@@ -47,17 +218,234 @@ enum CliCommand {
         /// The shell to generate completion for.
         shell: Shell,
     },
+    /// Cancel jobs with scancel, e.g. `turm kill 1234 1235_[1-5]`.
+    Kill {
+        #[arg(required = true, value_name = "JOBID", value_parser = job::parse_job_id)]
+        job_ids: Vec<String>,
+    },
+    /// Submit a batch script with sbatch and watch the new job, e.g.
+    /// `turm submit job.sh -- --partition=gpu`.
+    Submit {
+        script: PathBuf,
+        /// Passed on to sbatch.
+        #[arg(last = true, value_name = "SBATCH_ARGS")]
+        sbatch_args: Vec<String>,
+    },
+    /// Print the jobs once and exit, the squeue flags apply.
+    List {
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        format: OutputFormat,
+        /// Comma separated fields to print, available are job_id, name, state,
+        /// user, elapsed, time_limit, priority, partition, gpus and cpus.
+        #[arg(long, value_name = "FIELDS", value_delimiter = ',', value_parser = list::parse_field)]
+        columns: Vec<SortKey>,
+    },
+    /// Wait until a job finished. Exits with 0 if it completed, 1 if it
+    /// failed, was cancelled or ended otherwise, and 2 if its state is
+    /// unknown.
+    Wait {
+        #[arg(value_name = "JOBID", value_parser = job::parse_job_id)]
+        job_id: String,
+    },
+    /// Print the jobs that are new, finished or changed their state between
+    /// two snapshots of `--output json`. Exits with 0 if nothing changed, 1 if
+    /// something did and 2 if a snapshot can't be read.
+    Diff { before: PathBuf, after: PathBuf },
+    /// Show a snapshot of `--output json` in the TUI instead of polling
+    /// Slurm, e.g. to reproduce a layout problem.
+    Replay { snapshot: PathBuf },
+    /// Show or change settings of the config file.
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Print the value of KEY, from the config file or the default.
+    Get { key: String },
+    /// Set KEY to VALUE in the config file, e.g. `turm config set sort job-id`.
+    Set { key: String, value: String },
+    /// Print all keys with their values.
+    List,
 }
 
 fn main() -> Result<(), io::Error> {
-    let args = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let mut args = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    if let Err(e) = resolve_conflicts(&mut args, &matches) {
+        e.format(&mut Cli::command()).exit();
+    }
+    if args.version {
+        let squeue = args.squeue_binary.as_deref().unwrap_or(Path::new("squeue"));
+        println!(
+            "turm {} ({})",
+            env!("CARGO_PKG_VERSION"),
+            match cluster::slurm_version(squeue) {
+                Some(version) => format!("SLURM {}", version),
+                None => "squeue not available".to_owned(),
+            }
+        );
+        return Ok(());
+    }
+    let tui = matches!(
+        args.command,
+        None | Some(CliCommand::Submit { .. } | CliCommand::Replay { .. })
+    ) && args.output.is_none()
+        && !args.dry_run
+        && !args.init_config
+        && !args.no_tui;
+    if let Err(e) = logging::init(args.log_level, args.log_file.as_deref(), tui) {
+        eprintln!("Failed to open the log file {}", e);
+        process::exit(1);
+    }
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), tui, "starting turm");
+
     match args.command {
         Some(CliCommand::Completion { shell }) => {
             let cmd = &mut Cli::command();
             generate(shell, cmd, cmd.get_name().to_string(), &mut io::stdout());
             return Ok(());
         }
-        None => {}
+        Some(CliCommand::Kill { job_ids }) => {
+            tracing::debug!(?job_ids, "running kill");
+            let scancel = args
+                .scancel_binary
+                .as_deref()
+                .unwrap_or(Path::new("scancel"));
+            if !commands::kill(scancel, &job_ids) {
+                process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(CliCommand::Diff { before, after }) => {
+            tracing::debug!(?before, ?after, "running diff");
+            let (before, after) = match (diff::read_snapshot(&before), diff::read_snapshot(&after))
+            {
+                (Ok(before), Ok(after)) => (before, after),
+                (Err(e), _) | (_, Err(e)) => {
+                    eprintln!("{}", e);
+                    process::exit(2);
+                }
+            };
+            let changes = diff::diff(&before, &after);
+            print!("{}", diff::format(&changes, io::stdout().is_terminal()));
+            process::exit(if changes.is_empty() { 0 } else { 1 });
+        }
+        Some(CliCommand::Config { command }) => {
+            tracing::debug!("running config");
+            if let Err(e) = config_command(command) {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(
+            CliCommand::Submit { .. }
+            | CliCommand::List { .. }
+            | CliCommand::Wait { .. }
+            | CliCommand::Replay { .. },
+        )
+        | None => {}
+    }
+
+    if args.init_config {
+        match Config::init() {
+            Ok(path) => println!("Wrote {}", path.display()),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    let config = Config::load().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+    args.squeue_args
+        .set_default_filter_user(config.filter_user.clone());
+
+    if let Some(CliCommand::Wait { job_id }) = &args.command {
+        tracing::debug!(job_id, "running wait");
+        process::exit(commands::wait(job_id, job_watcher_config(&args, &config)));
+    }
+
+    if args.dry_run {
+        match job_watcher::squeue_command_line(&job_watcher_config(&args, &config)) {
+            Some(command) => println!("{}", command),
+            None => eprintln!("The watch file lists no jobs, squeue would not be run"),
+        }
+        return Ok(());
+    }
+
+    let (output, fields) = match &args.command {
+        Some(CliCommand::List { format, columns }) if !columns.is_empty() => {
+            (Some(format), &columns[..])
+        }
+        Some(CliCommand::List { format, .. }) => (Some(format), &list::DEFAULT_FIELDS[..]),
+        _ => (args.output.as_ref(), &list::DEFAULT_FIELDS[..]),
+    };
+    if let Some(format) = output {
+        tracing::debug!("printing the job list");
+        let jobs = match job_watcher::fetch_jobs(&job_watcher_config(&args, &config)) {
+            Ok(jobs) => jobs,
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        };
+        let jobs = job_filter(&args).apply(&jobs);
+        match format {
+            OutputFormat::Json => {
+                serde_json::to_writer_pretty(io::stdout(), &jobs)?;
+                println!();
+            }
+            OutputFormat::Text => print!("{}", list::text(&jobs, fields)),
+            OutputFormat::Csv => print!("{}", list::csv(&jobs, fields)),
+        }
+        return Ok(());
+    }
+
+    // the first job of an array or heterogeneous submission is selected
+    let submitted = match &args.command {
+        Some(CliCommand::Submit {
+            script,
+            sbatch_args,
+        }) => match commands::submit(script, sbatch_args) {
+            Ok(ids) => {
+                tracing::info!(?ids, "submitted");
+                ids.into_iter().next()
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        },
+        _ => None,
+    };
+
+    let feed = match (&args.command, &args.mock) {
+        (Some(CliCommand::Replay { snapshot }), _) => match replay::Snapshot::read(snapshot) {
+            Ok(snapshot) => JobFeed::Replay(snapshot),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        },
+        (_, Some(file)) => JobFeed::Mock(file.clone()),
+        _ => JobFeed::Slurm,
+    };
+
+    if args.no_tui {
+        tracing::debug!("printing job updates");
+        process::exit(commands::watch(
+            job_watcher_config(&args, &config),
+            &job_filter(&args),
+            slurm_refresh(&args, &config),
+        ));
     }
 
     // setup terminal
@@ -66,7 +454,7 @@ fn main() -> Result<(), io::Error> {
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
-    run_app(&mut terminal, args)?;
+    run_app(&mut terminal, args, config, submitted, feed)?;
 
     // restore terminal
     disable_raw_mode()?;
@@ -80,20 +468,159 @@ fn main() -> Result<(), io::Error> {
     Ok(())
 }
 
-fn input_loop(tx: Sender<std::io::Result<Event>>) {
+fn input_loop(tx: Sender<std::io::Result<Event>>, paused: Arc<AtomicBool>) {
     loop {
-        tx.send(event::read()).unwrap();
+        if paused.load(Ordering::SeqCst) {
+            thread::sleep(INPUT_POLL_INTERVAL);
+            continue;
+        }
+        match event::poll(INPUT_POLL_INTERVAL) {
+            Ok(true) => tx.send(event::read()).unwrap(),
+            Ok(false) => {}
+            Err(e) => tx.send(Err(e)).unwrap(),
+        }
     }
 }
 
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, args: Cli) -> io::Result<()> {
+fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    args: Cli,
+    config: Config,
+    select_job: Option<String>,
+    feed: JobFeed,
+) -> io::Result<()> {
     let (input_tx, input_rx) = unbounded();
+    let input_paused = Arc::new(AtomicBool::new(false));
+    let notify_on = match &args.notify_on[..] {
+        [] => config.notify_on.clone().unwrap_or_else(|| {
+            ["COMPLETED", "FAILED", "TIMEOUT"]
+                .map(String::from)
+                .to_vec()
+        }),
+        states => states.to_vec(),
+    };
+    let mut columns = config.columns.clone().unwrap_or_default();
+    if args.squeue_args.clusters().is_some_and(|c| !c.is_empty()) {
+        columns.show(Column::Cluster);
+    }
     let mut app = App::new(
         input_rx,
-        args.slurm_refresh,
-        args.file_refresh,
-        args.squeue_args.to_vec(),
+        input_paused.clone(),
+        AppConfig {
+            slurm_refresh_rate: slurm_refresh(&args, &config),
+            file_refresh_rate: args.file_refresh.or(config.file_refresh).unwrap_or(2),
+            job_watcher: job_watcher_config(&args, &config)
+                .sacct_enabled(sacct_enabled(&args, &config).then_some(args.since))
+                .filter(job_filter(&args)),
+            notify_on,
+            columns,
+            sort: config.sort_order(),
+            name_width: config.name_max_width.unwrap_or(30),
+            gpu_warning_after: Duration::from_secs(
+                config.gpu_warning_minutes.unwrap_or(5).saturating_mul(60),
+            ),
+            keybindings: config.keybindings.clone(),
+            theme: config
+                .theme
+                .unwrap_or_default()
+                .theme()
+                .with_depth(ColorDepth::detect()),
+            feed,
+        },
     );
-    thread::spawn(move || input_loop(input_tx));
+    if let Some(id) = select_job {
+        app.select_job(id);
+    }
+    if args.overview {
+        app.show_overview();
+    }
+    thread::spawn(move || input_loop(input_tx, input_paused));
     app.run(terminal)
 }
+
+/// `turm config`, `get` exits with 1 if the key has no value.
+fn config_command(command: ConfigCommand) -> io::Result<()> {
+    match command {
+        ConfigCommand::Get { key } => match Config::get(&key)? {
+            Some(value) => println!("{}", value),
+            None => process::exit(1),
+        },
+        ConfigCommand::Set { key, value } => Config::set(&key, &value)?,
+        ConfigCommand::List => {
+            for (key, value) in Config::list()? {
+                match value {
+                    Some(value) => println!("{} = {}", key, value),
+                    None => println!("# {} is not set", key),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn job_watcher_config(args: &Cli, config: &Config) -> JobWatcherConfig {
+    let mut watcher_config = JobWatcherConfig::default()
+        .interval(Duration::from_secs(slurm_refresh(args, config)))
+        .squeue_args(args.squeue_args.to_vec())
+        .watch_file(args.watch_file.clone())
+        .max_jobs(args.max_jobs.map(|n| n as usize))
+        .backoff(backoff(args, config));
+    if let Some(max_retries) = args.max_retries.or(config.max_retries) {
+        watcher_config = watcher_config.max_retries(max_retries);
+    }
+    if let Some(path) = &args.squeue_binary {
+        watcher_config = watcher_config.squeue_binary(path.clone());
+    }
+    if let Some(path) = &args.scontrol_binary {
+        watcher_config = watcher_config.scontrol_binary(path.clone());
+    }
+    if let Some(path) = &args.scancel_binary {
+        watcher_config = watcher_config.scancel_binary(path.clone());
+    }
+    watcher_config
+}
+
+fn resolve_conflicts(args: &mut Cli, matches: &ArgMatches) -> Result<(), clap::Error> {
+    args.squeue_args.resolve_conflicts(matches)?;
+    if args.watch_file.is_some() && args.squeue_args.has_job() {
+        match squeue_args::resolve_conflict(matches, "watch_file", "job")? {
+            "watch_file" => args.watch_file = None,
+            _ => args.squeue_args.clear_job(),
+        }
+    }
+    Ok(())
+}
+
+fn sacct_enabled(args: &Cli, config: &Config) -> bool {
+    match (args.sacct, args.no_sacct) {
+        (true, _) => true,
+        (_, true) => false,
+        _ => config.sacct.unwrap_or(false),
+    }
+}
+
+fn slurm_refresh(args: &Cli, config: &Config) -> u64 {
+    args.slurm_refresh.or(config.slurm_refresh).unwrap_or(2)
+}
+
+fn backoff(args: &Cli, config: &Config) -> Backoff {
+    let default = Backoff::default();
+    Backoff {
+        max_delay: args
+            .max_backoff
+            .or(config.max_backoff)
+            .map(Duration::from_secs)
+            .unwrap_or(default.max_delay),
+    }
+}
+
+fn job_filter(args: &Cli) -> JobFilter {
+    JobFilter {
+        state: args.filter_state.clone(),
+        qos: args.filter_qos.clone(),
+        account: args.filter_account.clone(),
+        reservation: args.filter_reservation.clone(),
+        name_regex: args.filter_name.clone(),
+        ..Default::default()
+    }
+}
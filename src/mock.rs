@@ -0,0 +1,82 @@
+use std::{path::PathBuf, thread, time::Duration};
+
+use crossbeam::{
+    channel::{unbounded, Receiver, Sender},
+    select,
+};
+
+use crate::app::AppMessage;
+use crate::diff::{jobs_changed, JobChange};
+use crate::job::Job;
+use crate::replay::Snapshot;
+
+/// Stands in for the job watcher with `--mock`: sends the jobs of a file
+/// written by `--output json` instead of running squeue. The file is read on
+/// every poll, so it can be edited while turm is running, e.g. to try out
+/// `--notify-on` by changing the state of a job.
+struct MockJobWatcher {
+    app: Sender<AppMessage>,
+    shutdown: Receiver<()>,
+    file: PathBuf,
+    interval: Duration,
+    /// The jobs of the last read.
+    prev_jobs: Vec<Job>,
+}
+
+/// Dropping the handle stops the watcher.
+pub struct MockJobWatcherHandle {
+    _shutdown: Sender<()>,
+}
+
+impl MockJobWatcher {
+    fn run(&mut self) {
+        loop {
+            let message = match Snapshot::read(&self.file) {
+                Ok(snapshot) => {
+                    for change in jobs_changed(&self.prev_jobs, &snapshot.jobs) {
+                        if let JobChange::StateChanged { job, old_state } = change {
+                            let message = AppMessage::JobStateChanged {
+                                id: job.id(),
+                                old_state,
+                                new_state: job.state,
+                            };
+                            if self.app.send(message).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    self.prev_jobs = snapshot.jobs.clone();
+                    AppMessage::Jobs(snapshot.jobs)
+                }
+                // shown like a failed squeue, the next poll may succeed
+                Err(e) => AppMessage::SqueueError(e),
+            };
+            if self.app.send(message).is_err() {
+                return;
+            }
+            select! {
+                // disconnected once the handle is dropped
+                recv(self.shutdown) -> _ => return,
+                default(self.interval) => {}
+            }
+        }
+    }
+}
+
+impl MockJobWatcherHandle {
+    pub fn new(app: Sender<AppMessage>, file: PathBuf, interval: Duration) -> Self {
+        let (shutdown, receiver) = unbounded();
+        let mut actor = MockJobWatcher {
+            app,
+            shutdown: receiver,
+            file,
+            interval,
+            prev_jobs: Vec::new(),
+        };
+        thread::spawn(move || actor.run());
+
+        Self {
+            _shutdown: shutdown,
+        }
+    }
+}
@@ -0,0 +1,122 @@
+/// Expands a Slurm host list such as `node[01-04,07],gpu1` into the
+/// individual host names. Hosts can have several bracket groups, e.g.
+/// `rack[1-2]-node[1-3]`, and a group may contain further groups.
+pub fn expand(nodelist: &str) -> Vec<String> {
+    match nodelist.trim() {
+        "" | "(null)" | "(None)" | "None assigned" | "N/A" => Vec::new(),
+        nodelist => split_top_level(nodelist)
+            .into_iter()
+            .filter(|h| !h.is_empty())
+            .flat_map(expand_host)
+            .collect(),
+    }
+}
+
+/// Splits at commas that are not inside brackets.
+fn split_top_level(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+fn expand_host(host: &str) -> Vec<String> {
+    let Some(open) = host.find('[') else {
+        return vec![host.to_owned()];
+    };
+    let Some(close) = matching_bracket(host, open) else {
+        // not a valid pattern, keep it as it is
+        return vec![host.to_owned()];
+    };
+    let (prefix, group, suffix) = (&host[..open], &host[open + 1..close], &host[close + 1..]);
+
+    let suffixes = expand_host(suffix);
+    split_top_level(group)
+        .into_iter()
+        .flat_map(expand_range)
+        .flat_map(|value| {
+            suffixes
+                .iter()
+                .map(move |suffix| format!("{}{}{}", prefix, value, suffix))
+        })
+        .collect()
+}
+
+fn matching_bracket(s: &str, open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices().skip_while(|(i, _)| *i < open) {
+        match c {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// `01-03` becomes `01`, `02`, `03`, keeping the zero padding of the start.
+fn expand_range(range: &str) -> Vec<String> {
+    if range.contains('[') {
+        return expand_host(range);
+    }
+    let Some((start, end)) = range.split_once('-') else {
+        return vec![range.to_owned()];
+    };
+    match (start.parse::<u64>(), end.parse::<u64>()) {
+        (Ok(first), Ok(last)) if first <= last => (first..=last)
+            .map(|n| format!("{:0width$}", n, width = start.len()))
+            .collect(),
+        _ => vec![range.to_owned()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        assert_eq!(expand("node01"), ["node01"]);
+        assert_eq!(expand("node01,node02"), ["node01", "node02"]);
+        assert_eq!(
+            expand("node[01-04,07]"),
+            ["node01", "node02", "node03", "node04", "node07"]
+        );
+        assert_eq!(expand("gpu[8-10],cpu1"), ["gpu8", "gpu9", "gpu10", "cpu1"]);
+        assert_eq!(expand(""), Vec::<String>::new());
+        assert_eq!(expand("None assigned"), Vec::<String>::new());
+        assert_eq!(expand("(None)"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_expand_multiple_groups() {
+        assert_eq!(
+            expand("rack[1-2]-node[1,3]"),
+            ["rack1-node1", "rack1-node3", "rack2-node1", "rack2-node3"]
+        );
+        assert_eq!(expand("n[a[1-2],b]"), ["na1", "na2", "nb"]);
+    }
+
+    #[test]
+    fn test_expand_invalid() {
+        assert_eq!(expand("node[01-"), ["node[01-"]);
+        assert_eq!(expand("node[5-3]"), ["node5-3"]);
+    }
+}
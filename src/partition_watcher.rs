@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::{io::BufRead, process::Command, thread, time::Duration};
+
+use crossbeam::{
+    channel::{unbounded, Receiver, Sender},
+    select,
+};
+
+use crate::app::AppMessage;
+use crate::job::output_separator;
+
+const SINFO_FIELDS: [&str; 5] = ["partitionname", "available", "nodes", "cpusstate", "gres"];
+
+struct PartitionWatcher {
+    app: Sender<AppMessage>,
+    shutdown: Receiver<()>,
+    interval: Duration,
+    output_separator: String,
+}
+
+/// Dropping the handle stops the watcher.
+pub struct PartitionWatcherHandle {
+    _shutdown: Sender<()>,
+}
+
+pub struct Partition {
+    pub name: String,
+    pub state: String,
+    pub nodes: u32,
+    pub cpus_total: u32,
+    pub cpus_idle: u32,
+    pub gpus_total: u32,
+}
+
+impl PartitionWatcher {
+    fn new(app: Sender<AppMessage>, shutdown: Receiver<()>, interval: Duration) -> Self {
+        Self {
+            app,
+            shutdown,
+            interval,
+            output_separator: output_separator(),
+        }
+    }
+
+    fn run(&mut self) {
+        let output_format = SINFO_FIELDS
+            .map(|s| s.to_owned() + ":" + &self.output_separator)
+            .join(",");
+
+        loop {
+            // an unavailable sinfo only leaves the partitions tab empty
+            if let Ok(output) = Command::new("sinfo")
+                .arg("--noheader")
+                .arg("--Format")
+                .arg(&output_format)
+                .output()
+            {
+                let partitions = parse_sinfo(
+                    output.stdout.lines().map_while(Result::ok),
+                    &self.output_separator,
+                );
+                self.app.send(AppMessage::Partitions(partitions)).unwrap();
+            }
+            select! {
+                // disconnected once the handle is dropped
+                recv(self.shutdown) -> _ => return,
+                default(self.interval) => {}
+            }
+        }
+    }
+}
+
+/// sinfo prints one line per group of nodes with the same configuration, so
+/// lines of the same partition are summed up.
+fn parse_sinfo(lines: impl Iterator<Item = String>, separator: &str) -> Vec<Partition> {
+    let mut partitions: Vec<Partition> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for line in lines {
+        let parts: Vec<_> = line.trim().split(separator).collect();
+        if parts.len() != SINFO_FIELDS.len() + 1 {
+            continue;
+        }
+
+        let name = parts[0];
+        let state = parts[1];
+        let Ok(nodes) = parts[2].parse::<u32>() else {
+            continue;
+        };
+        // allocated/idle/other/total
+        let cpus: Vec<u32> = parts[3].split('/').filter_map(|c| c.parse().ok()).collect();
+        let (cpus_idle, cpus_total) = match cpus[..] {
+            [_, idle, _, total] => (idle, total),
+            _ => (0, 0),
+        };
+        let gpus_total = nodes.saturating_mul(gpus_per_node(parts[4]));
+
+        let i = *index.entry(name.to_owned()).or_insert_with(|| {
+            partitions.push(Partition {
+                name: name.to_owned(),
+                state: state.to_owned(),
+                nodes: 0,
+                cpus_total: 0,
+                cpus_idle: 0,
+                gpus_total: 0,
+            });
+            partitions.len() - 1
+        });
+        let p = &mut partitions[i];
+        p.nodes = p.nodes.saturating_add(nodes);
+        p.cpus_total = p.cpus_total.saturating_add(cpus_total);
+        p.cpus_idle = p.cpus_idle.saturating_add(cpus_idle);
+        p.gpus_total = p.gpus_total.saturating_add(gpus_total);
+    }
+
+    partitions
+}
+
+/// Parses a GRES string such as `gpu:a100:4(S:0-1),shard:8` or `(null)`.
+fn gpus_per_node(gres: &str) -> u32 {
+    gres.split(',')
+        .filter(|g| g.starts_with("gpu"))
+        .filter_map(|g| {
+            let g = g.split('(').next().unwrap_or(g);
+            g.rsplit(':').next().and_then(|c| c.parse::<u32>().ok())
+        })
+        .fold(0, u32::saturating_add)
+}
+
+impl PartitionWatcherHandle {
+    pub fn new(app: Sender<AppMessage>, interval: Duration) -> Self {
+        let (shutdown, receiver) = unbounded();
+        let mut actor = PartitionWatcher::new(app, receiver, interval);
+        thread::spawn(move || actor.run());
+
+        Self {
+            _shutdown: shutdown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sinfo() {
+        let lines = [
+            "gpu|up|2|8/56/0/64|gpu:a100:4(S:0-1)|",
+            "gpu|up|1|0/32/0/32|gpu:a100:4(S:0-1),shard:8|",
+            "cpu|up|10|100/540/0/640|(null)|",
+            "broken|up|",
+        ]
+        .map(String::from);
+
+        let partitions = parse_sinfo(lines.into_iter(), "|");
+        assert_eq!(partitions.len(), 2);
+
+        assert_eq!(partitions[0].name, "gpu");
+        assert_eq!(partitions[0].nodes, 3);
+        assert_eq!(partitions[0].cpus_total, 96);
+        assert_eq!(partitions[0].cpus_idle, 88);
+        assert_eq!(partitions[0].gpus_total, 12);
+
+        assert_eq!(partitions[1].name, "cpu");
+        assert_eq!(partitions[1].state, "up");
+        assert_eq!(partitions[1].gpus_total, 0);
+
+        // saturates instead of overflowing
+        let lines = [
+            "big|up|4294967295|0/1/0/1|gpu:8|",
+            "big|up|1|0/1/0/1|gpu:8|",
+        ]
+        .map(String::from);
+        let partitions = parse_sinfo(lines.into_iter(), "|");
+        assert_eq!(partitions[0].nodes, u32::MAX);
+        assert_eq!(partitions[0].gpus_total, u32::MAX);
+    }
+}
@@ -0,0 +1,186 @@
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Why a job is in its current state, as reported by `squeue -O reason`.
+///
+/// See https://slurm.schedmd.com/job_reason_codes.html for all codes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JobReason {
+    AssocGrpCpuLimit,
+    AssocGrpGRES,
+    AssocMaxJobsLimit,
+    BadConstraints,
+    BeginTime,
+    Dependency,
+    DependencyNeverSatisfied,
+    InvalidAccount,
+    InvalidQOS,
+    JobHeldAdmin,
+    JobHeldUser,
+    NodeDown,
+    PartitionDown,
+    PartitionInactive,
+    PartitionNodeLimit,
+    PartitionTimeLimit,
+    Priority,
+    QOSMaxCpuPerUserLimit,
+    QOSMaxJobsPerUserLimit,
+    ReqNodeNotAvail,
+    Reservation,
+    Resources,
+    Other(String),
+}
+
+impl JobReason {
+    /// Returns `None` for Slurm's `None` reason.
+    pub fn parse(s: &str) -> Option<Self> {
+        let reason = match s {
+            "None" | "" => return None,
+            "AssocGrpCpuLimit" => JobReason::AssocGrpCpuLimit,
+            "AssocGrpGRES" => JobReason::AssocGrpGRES,
+            "AssocMaxJobsLimit" => JobReason::AssocMaxJobsLimit,
+            "BadConstraints" => JobReason::BadConstraints,
+            "BeginTime" => JobReason::BeginTime,
+            "Dependency" => JobReason::Dependency,
+            "DependencyNeverSatisfied" => JobReason::DependencyNeverSatisfied,
+            "InvalidAccount" => JobReason::InvalidAccount,
+            "InvalidQOS" => JobReason::InvalidQOS,
+            "JobHeldAdmin" => JobReason::JobHeldAdmin,
+            "JobHeldUser" => JobReason::JobHeldUser,
+            "NodeDown" => JobReason::NodeDown,
+            "PartitionDown" => JobReason::PartitionDown,
+            "PartitionInactive" => JobReason::PartitionInactive,
+            "PartitionNodeLimit" => JobReason::PartitionNodeLimit,
+            "PartitionTimeLimit" => JobReason::PartitionTimeLimit,
+            "Priority" => JobReason::Priority,
+            "QOSMaxCpuPerUserLimit" => JobReason::QOSMaxCpuPerUserLimit,
+            "QOSMaxJobsPerUserLimit" => JobReason::QOSMaxJobsPerUserLimit,
+            "ReqNodeNotAvail" => JobReason::ReqNodeNotAvail,
+            "Reservation" => JobReason::Reservation,
+            "Resources" => JobReason::Resources,
+            other => JobReason::Other(other.to_owned()),
+        };
+        Some(reason)
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            JobReason::AssocGrpCpuLimit => "AssocGrpCpuLimit",
+            JobReason::AssocGrpGRES => "AssocGrpGRES",
+            JobReason::AssocMaxJobsLimit => "AssocMaxJobsLimit",
+            JobReason::BadConstraints => "BadConstraints",
+            JobReason::BeginTime => "BeginTime",
+            JobReason::Dependency => "Dependency",
+            JobReason::DependencyNeverSatisfied => "DependencyNeverSatisfied",
+            JobReason::InvalidAccount => "InvalidAccount",
+            JobReason::InvalidQOS => "InvalidQOS",
+            JobReason::JobHeldAdmin => "JobHeldAdmin",
+            JobReason::JobHeldUser => "JobHeldUser",
+            JobReason::NodeDown => "NodeDown",
+            JobReason::PartitionDown => "PartitionDown",
+            JobReason::PartitionInactive => "PartitionInactive",
+            JobReason::PartitionNodeLimit => "PartitionNodeLimit",
+            JobReason::PartitionTimeLimit => "PartitionTimeLimit",
+            JobReason::Priority => "Priority",
+            JobReason::QOSMaxCpuPerUserLimit => "QOSMaxCpuPerUserLimit",
+            JobReason::QOSMaxJobsPerUserLimit => "QOSMaxJobsPerUserLimit",
+            JobReason::ReqNodeNotAvail => "ReqNodeNotAvail",
+            JobReason::Reservation => "Reservation",
+            JobReason::Resources => "Resources",
+            JobReason::Other(s) => s,
+        }
+    }
+}
+
+/// A plain explanation of the reason, empty for unknown reasons.
+pub fn reason_description(reason: &JobReason) -> &'static str {
+    match reason {
+        JobReason::AssocGrpCpuLimit => "Your account uses all the CPUs it is allowed to.",
+        JobReason::AssocGrpGRES => "Your account uses all the GPUs or other GRES it is allowed to.",
+        JobReason::AssocMaxJobsLimit => "Your account runs as many jobs as it is allowed to.",
+        JobReason::BadConstraints => "No node can satisfy the constraints of the job.",
+        JobReason::BeginTime => "The job was submitted with a later start time.",
+        JobReason::Dependency => "The job waits for the jobs it depends on to finish.",
+        JobReason::DependencyNeverSatisfied => {
+            "A job this job depends on failed, so it will never start."
+        }
+        JobReason::InvalidAccount => "The account of the job is invalid.",
+        JobReason::InvalidQOS => "The QOS of the job is invalid.",
+        JobReason::JobHeldAdmin => "An administrator holds the job.",
+        JobReason::JobHeldUser => "The job is held, release it to let it start.",
+        JobReason::NodeDown => "A node the job needs is down.",
+        JobReason::PartitionDown => "The partition is down.",
+        JobReason::PartitionInactive => "The partition does not accept jobs right now.",
+        JobReason::PartitionNodeLimit => "The job asks for more nodes than the partition allows.",
+        JobReason::PartitionTimeLimit => "The time limit of the job exceeds the partition's.",
+        JobReason::Priority => "Jobs with a higher priority are scheduled first.",
+        JobReason::QOSMaxCpuPerUserLimit => "You use all the CPUs your QOS allows.",
+        JobReason::QOSMaxJobsPerUserLimit => "You run as many jobs as your QOS allows.",
+        JobReason::ReqNodeNotAvail => "A node the job needs is not available.",
+        JobReason::Reservation => "The job waits for its reservation to start.",
+        JobReason::Resources => "The job waits for enough free resources.",
+        // e.g. `ReqNodeNotAvail, UnavailableNodes:node01`
+        JobReason::Other(s) if s.starts_with("ReqNodeNotAvail") => {
+            reason_description(&JobReason::ReqNodeNotAvail)
+        }
+        JobReason::Other(_) => "",
+    }
+}
+
+impl fmt::Display for JobReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for JobReason {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for JobReason {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(JobReason::parse(&s).unwrap_or(JobReason::Other(s)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reason() {
+        assert_eq!(JobReason::parse("None"), None);
+        assert_eq!(JobReason::parse("Resources"), Some(JobReason::Resources));
+        assert_eq!(
+            JobReason::parse("DependencyNeverSatisfied"),
+            Some(JobReason::DependencyNeverSatisfied)
+        );
+
+        let reason = JobReason::parse("ReqNodeNotAvail, UnavailableNodes:node01").unwrap();
+        assert_eq!(
+            reason,
+            JobReason::Other("ReqNodeNotAvail, UnavailableNodes:node01".to_string())
+        );
+        assert_eq!(
+            reason.to_string(),
+            "ReqNodeNotAvail, UnavailableNodes:node01"
+        );
+    }
+
+    #[test]
+    fn test_reason_description() {
+        assert_eq!(
+            reason_description(&JobReason::Priority),
+            "Jobs with a higher priority are scheduled first."
+        );
+        assert_eq!(
+            reason_description(&JobReason::parse("ReqNodeNotAvail, UnavailableNodes:n1").unwrap()),
+            reason_description(&JobReason::ReqNodeNotAvail)
+        );
+        assert_eq!(reason_description(&JobReason::Other("Foo".to_owned())), "");
+    }
+}
@@ -0,0 +1,28 @@
+use std::{fs, path::Path};
+
+use chrono::{DateTime, Local};
+
+use crate::job::Job;
+
+/// A job list written by `--output json` or the export key, for `turm
+/// replay`.
+pub struct Snapshot {
+    pub jobs: Vec<Job>,
+    /// When the file was last modified.
+    pub taken: DateTime<Local>,
+}
+
+impl Snapshot {
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let error = |e: &dyn std::fmt::Display| format!("{}: {}", path.display(), e);
+        let json = fs::read_to_string(path).map_err(|e| error(&e))?;
+        let jobs = serde_json::from_str(&json).map_err(|e| error(&e))?;
+        let taken = fs::metadata(path)
+            .and_then(|m| m.modified())
+            .map_err(|e| error(&e))?;
+        Ok(Self {
+            jobs,
+            taken: taken.into(),
+        })
+    }
+}
@@ -0,0 +1,189 @@
+use std::{io::BufRead, process::Command, thread, time::Duration};
+
+use chrono::Local;
+
+use crossbeam::{
+    channel::{unbounded, Receiver, Sender},
+    select,
+};
+
+use crate::app::AppMessage;
+use crate::job::Job;
+
+const SACCT_FIELDS: [&str; 16] = [
+    "JobID",
+    "JobIDRaw",
+    "JobName",
+    "State",
+    "User",
+    "Elapsed",
+    "AllocTRES",
+    "Partition",
+    "QOS",
+    "Account",
+    "Reservation",
+    "NodeList",
+    "WorkDir",
+    "Submit",
+    "Start",
+    "Cluster",
+];
+
+struct SacctWatcher {
+    app: Sender<AppMessage>,
+    shutdown: Receiver<()>,
+    interval: Duration,
+    /// Only jobs that started within this window.
+    since: Duration,
+}
+
+/// Longer windows can make sacct return huge amounts of jobs.
+const MAX_SINCE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Parses a duration like `90m`, `24h` or `7d` for `--since`, at most 30 days.
+pub fn parse_since(s: &str) -> Result<Duration, String> {
+    let unit_start = s
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("missing unit in `{}`, e.g. `24h`", s))?;
+    let (n, unit) = s.split_at(unit_start);
+    let n: u64 = n.parse().map_err(|_| format!("invalid duration `{}`", s))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(format!("unknown unit `{}`, use s, m, h, d or w", unit)),
+    };
+    let since = Duration::from_secs(n.saturating_mul(seconds));
+    if since.is_zero() {
+        return Err("duration must be positive".to_owned());
+    }
+    if since > MAX_SINCE {
+        return Err("duration must be at most 30 days".to_owned());
+    }
+    Ok(since)
+}
+
+/// Watches `sacct` for jobs that already left the queue. Dropping the handle
+/// stops the watcher.
+pub struct SacctWatcherHandle {
+    _shutdown: Sender<()>,
+}
+
+impl SacctWatcher {
+    fn new(
+        app: Sender<AppMessage>,
+        shutdown: Receiver<()>,
+        interval: Duration,
+        since: Duration,
+    ) -> Self {
+        Self {
+            app,
+            shutdown,
+            interval,
+            since,
+        }
+    }
+
+    fn run(&mut self) {
+        let format = SACCT_FIELDS.join(",");
+
+        loop {
+            let message = match self.query_sacct(&format) {
+                Ok(jobs) => AppMessage::SacctJobs(jobs),
+                // the next poll may succeed
+                Err(e) => AppMessage::SacctError(e),
+            };
+            if self.app.send(message).is_err() {
+                return;
+            }
+            select! {
+                // disconnected once the handle is dropped
+                recv(self.shutdown) -> _ => return,
+                default(self.interval) => {}
+            }
+        }
+    }
+}
+
+impl SacctWatcher {
+    fn query_sacct(&self, format: &str) -> Result<Vec<Job>, String> {
+        // sacct takes local time
+        let start = Local::now() - self.since;
+        let output = Command::new("sacct")
+            .arg("--starttime")
+            .arg(start.format("%Y-%m-%dT%H:%M:%S").to_string())
+            .arg("--noheader")
+            .arg("--parsable2")
+            .arg("--allocations")
+            .arg("--format")
+            .arg(format)
+            .output()
+            .map_err(|e| format!("Failed to execute sacct: {}", e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(match stderr.trim() {
+                "" => format!("sacct failed: {}", output.status),
+                stderr => stderr.to_owned(),
+            });
+        }
+        Ok(output
+            .stdout
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|l| Job::from_sacct_line(&l, &SACCT_FIELDS))
+            .collect())
+    }
+}
+
+/// The IDs (`1234`, `1234_5`) and states of the jobs and their array tasks
+/// according to sacct, `job_ids` is a comma separated list. Empty if sacct
+/// does not know the jobs or can't be run.
+pub fn sacct_states(job_ids: &str) -> Vec<(String, String)> {
+    const FIELDS: [&str; 2] = ["JobID", "State"];
+    let Ok(output) = Command::new("sacct")
+        .args(["--noheader", "--parsable2", "--allocations", "-j", job_ids])
+        .arg("--format")
+        .arg(FIELDS.join(","))
+        .output()
+    else {
+        return Vec::new();
+    };
+    output
+        .stdout
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|l| Job::from_sacct_line(&l, &FIELDS))
+        .map(|j| (j.id(), j.state))
+        .collect()
+}
+
+impl SacctWatcherHandle {
+    pub fn new(app: Sender<AppMessage>, interval: Duration, since: Duration) -> Self {
+        let (shutdown, receiver) = unbounded();
+        let mut actor = SacctWatcher::new(app, receiver, interval, since);
+        thread::spawn(move || actor.run());
+
+        Self {
+            _shutdown: shutdown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_since() {
+        assert_eq!(parse_since("90m"), Ok(Duration::from_secs(90 * 60)));
+        assert_eq!(parse_since("24h"), Ok(Duration::from_secs(24 * 60 * 60)));
+        assert_eq!(parse_since("30d"), Ok(MAX_SINCE));
+        assert!(parse_since("31d").is_err());
+        assert!(parse_since("0h").is_err());
+        assert!(parse_since("24").is_err());
+        assert!(parse_since("1y").is_err());
+        assert!(parse_since("-1h").is_err());
+    }
+}
@@ -0,0 +1,156 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::job::Job;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortKey {
+    JobId,
+    Name,
+    State,
+    User,
+    Elapsed,
+    TimeLimit,
+    Priority,
+    Partition,
+    Gpu,
+    Cpu,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 10] = [
+        SortKey::JobId,
+        SortKey::Name,
+        SortKey::State,
+        SortKey::User,
+        SortKey::Elapsed,
+        SortKey::TimeLimit,
+        SortKey::Priority,
+        SortKey::Partition,
+        SortKey::Gpu,
+        SortKey::Cpu,
+    ];
+
+    /// The key pressed after `s` to sort by this column.
+    pub fn key(&self) -> char {
+        match self {
+            SortKey::JobId => 'i',
+            SortKey::Name => 'n',
+            SortKey::State => 's',
+            SortKey::User => 'u',
+            SortKey::Elapsed => 't',
+            SortKey::TimeLimit => 'l',
+            SortKey::Priority => 'r',
+            SortKey::Partition => 'p',
+            SortKey::Gpu => 'g',
+            SortKey::Cpu => 'c',
+        }
+    }
+
+    pub fn from_key(c: char) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.key() == c)
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            SortKey::JobId => "id",
+            SortKey::Name => "name",
+            SortKey::State => "state",
+            SortKey::User => "user",
+            SortKey::Elapsed => "time",
+            SortKey::TimeLimit => "time limit",
+            SortKey::Priority => "priority",
+            SortKey::Partition => "partition",
+            SortKey::Gpu => "gpus",
+            SortKey::Cpu => "cpus",
+        }
+    }
+
+    fn compare(&self, a: &Job, b: &Job) -> Ordering {
+        match self {
+            SortKey::JobId => job_id_key(a).cmp(&job_id_key(b)),
+            SortKey::Name => a.name.cmp(&b.name),
+            SortKey::State => a.state.cmp(&b.state),
+            SortKey::User => a.user.cmp(&b.user),
+            SortKey::Elapsed => a.elapsed_seconds().cmp(&b.elapsed_seconds()),
+            SortKey::TimeLimit => a.time_limit_seconds().cmp(&b.time_limit_seconds()),
+            SortKey::Priority => a.priority.cmp(&b.priority),
+            SortKey::Partition => a.partition.cmp(&b.partition),
+            SortKey::Gpu => a.gpu_count().cmp(&b.gpu_count()),
+            SortKey::Cpu => a.cpus().cmp(&b.cpus()),
+        }
+    }
+}
+
+/// Array tasks are sorted by their array job, e.g. `9_10` after `9_2`.
+fn job_id_key(job: &Job) -> (Option<u64>, Option<u64>, String) {
+    (
+        job.array_id.parse().ok(),
+        job.array_step.as_ref().and_then(|s| s.parse().ok()),
+        job.id(),
+    )
+}
+
+/// Stable, so jobs with equal keys keep their squeue order.
+pub fn sort_jobs(jobs: &mut [Job], key: SortKey, ascending: bool) {
+    jobs.sort_by(|a, b| {
+        let ordering = key.compare(a, b);
+        if ascending {
+            ordering
+        } else {
+            ordering.reverse()
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(array_id: &str, array_step: Option<&str>, name: &str, tres: &str) -> Job {
+        Job {
+            job_id: array_id.to_owned(),
+            array_id: array_id.to_owned(),
+            array_step: array_step.map(str::to_owned),
+            name: name.to_owned(),
+            tres: tres.to_owned(),
+            ..Job::test_default()
+        }
+    }
+
+    fn ids(jobs: &[Job]) -> Vec<String> {
+        jobs.iter().map(|j| j.id()).collect()
+    }
+
+    #[test]
+    fn test_sort_jobs() {
+        let mut jobs = vec![
+            job("100", Some("10"), "b", "cpu=4"),
+            job("20", None, "c", "cpu=1,gres/gpu=2"),
+            job("100", Some("2"), "a", "cpu=8"),
+        ];
+
+        sort_jobs(&mut jobs, SortKey::JobId, true);
+        assert_eq!(ids(&jobs), ["20", "100_2", "100_10"]);
+
+        sort_jobs(&mut jobs, SortKey::Name, false);
+        assert_eq!(ids(&jobs), ["20", "100_10", "100_2"]);
+
+        sort_jobs(&mut jobs, SortKey::Cpu, true);
+        assert_eq!(ids(&jobs), ["20", "100_10", "100_2"]);
+
+        // jobs without GPUs first
+        sort_jobs(&mut jobs, SortKey::Gpu, true);
+        assert_eq!(ids(&jobs), ["100_10", "100_2", "20"]);
+    }
+
+    #[test]
+    fn test_sort_key_from_key() {
+        for key in SortKey::ALL {
+            assert_eq!(SortKey::from_key(key.key()), Some(key));
+        }
+        assert_eq!(SortKey::from_key('x'), None);
+    }
+}
@@ -1,95 +1,184 @@
-use clap::Args;
+use std::{env, process::Command};
+
+use clap::{builder::FalseyValueParser, error::ErrorKind, parser::ValueSource, ArgMatches, Args};
+
+/// Arguments that can't be used together. clap's `conflicts_with` would
+/// also reject a flag that only conflicts with an environment variable, see
+/// [`resolve_conflict`].
+const CONFLICTS: [(&str, &str); 6] = [
+    ("filter_user", "user"),
+    ("filter_user", "me"),
+    ("all_users", "filter_user"),
+    ("all_users", "user"),
+    ("all_users", "me"),
+    ("filter_partition", "partition"),
+];
+
 /// Doc comment
 #[derive(Args, Debug)]
 pub struct SqueueArgs {
     /// |squeue arg| Comma separated list of accounts to view, default is all accounts.
-    #[arg(short = 'A', long)]
+    #[arg(short = 'A', long, env = "TURM_ACCOUNT")]
     account: Option<String>,
 
     /// |squeue arg| Display jobs in hidden partitions.
-    #[arg(short, long)]
+    #[arg(short, long, value_parser = FalseyValueParser::new(), env = "TURM_ALL")]
     all: bool,
 
     /// |squeue arg| Report federated information if a member of one.
-    #[arg(long)]
+    #[arg(long, value_parser = FalseyValueParser::new(), env = "TURM_FEDERATION")]
     federation: bool,
 
     /// |squeue arg| Do not display jobs in hidden partitions.
-    #[arg(long)]
+    #[arg(long, value_parser = FalseyValueParser::new(), env = "TURM_HIDE")]
     hide: bool,
 
     /// |squeue arg| Comma separated list of jobs IDs to view, default is all.
-    #[arg(short, long, value_name = "JOBID")]
+    #[arg(short, long, value_name = "JOBID", env = "TURM_JOB")]
     job: Option<String>,
 
     /// |squeue arg| Report information only about jobs on the local cluster. Overrides `--federation`.
-    #[arg(long)]
+    #[arg(long, value_parser = FalseyValueParser::new(), env = "TURM_LOCAL")]
     local: bool,
 
     /// |squeue arg| Comma separated list of license names to view.
-    #[arg(short = 'L', long)]
+    #[arg(short = 'L', long, env = "TURM_LICENSES")]
     licenses: Option<String>,
 
-    /// |squeue arg| Cluster to issue commands to. Default is current cluster. Cluster with no name will reset to default. Implies `--local`.
-    #[arg(short = 'M', long)]
+    /// |squeue arg| Comma separated list of clusters to issue commands to. Default is current cluster. Cluster with no name will reset to default. Implies `--local`. Shows the cluster column.
+    #[arg(short = 'M', long, visible_alias = "cluster", env = "TURM_CLUSTERS")]
     clusters: Option<String>,
 
     /// |squeue arg| Equivalent to `--user=<my username>`.
-    #[arg(long)]
+    #[arg(long, value_parser = FalseyValueParser::new(), env = "TURM_ME")]
     me: bool,
 
     /// |squeue arg| Comma separated list of job names to view.
-    #[arg(short = 'n', long)]
+    #[arg(short = 'n', long, env = "TURM_NAME")]
     name: Option<String>,
 
     /// |squeue arg| Don't convert units from their original type (e.g. 2048M won't be converted to 2G).
-    #[arg(long)]
+    #[arg(long, value_parser = FalseyValueParser::new(), env = "TURM_NOCONVERT")]
     noconvert: bool,
 
     /// |squeue arg| Comma separated list of partitions to view, default is all partitions.
-    #[arg(short, long)]
+    #[arg(short, long, value_parser = parse_partitions, env = "TURM_PARTITION")]
     partition: Option<String>,
 
     /// |squeue arg| Comma separated list of qos's to view, default is all qos's.
-    #[arg(short, long)]
+    #[arg(short, long, env = "TURM_QOS")]
     qos: Option<String>,
 
     /// |squeue arg| Reservation to view, default is all.
-    #[arg(short = 'R', long)]
+    #[arg(short = 'R', long, env = "TURM_RESERVATION")]
     reservation: Option<String>,
 
     /// |squeue arg| Report information about all sibling jobs on a federated cluster. Implies --federation.
-    #[arg(long)]
+    #[arg(long, value_parser = FalseyValueParser::new(), env = "TURM_SIBLING")]
     sibling: bool,
 
     /// |squeue arg| Comma separated list of job steps to view, default is all.
-    #[arg(short, long)]
+    #[arg(short, long, env = "TURM_STEP")]
     step: Option<String>,
 
     /// |squeue arg| Comma separated list of fields to sort on.
-    #[arg(short = 'S', long, value_name = "FIELDS")]
+    #[arg(short = 'S', long, value_name = "FIELDS", env = "TURM_SORT")]
     sort: Option<String>,
 
     /// |squeue arg| Comma separated list of states to view, default is pending and running, `--states=all` reports all states.
-    #[arg(short = 't', long)]
+    #[arg(short = 't', long, env = "TURM_STATES")]
     states: Option<String>,
 
     /// |squeue arg| Comma separated list of users to view.
-    #[arg(short = 'u', long)]
+    #[arg(short = 'u', long, env = "TURM_USER")]
     user: Option<String>,
 
     /// |squeue arg| List of nodes to view, default is all nodes.
-    #[arg(short = 'w', long, value_name = "NODES")]
+    #[arg(short = 'w', long, value_name = "NODES", env = "TURM_NODELIST")]
     nodelist: Option<String>,
+
+    /// Only show jobs of USER, `me` is the current user.
+    #[arg(long, value_name = "USER", env = "TURM_FILTER_USER")]
+    filter_user: Option<String>,
+
+    /// Show the jobs of all users, even if squeue is configured to only show your own. Implies `--all`.
+    #[arg(long, value_parser = FalseyValueParser::new(), env = "TURM_ALL_USERS")]
+    all_users: bool,
+
+    /// Only show jobs in PARTITIONS (comma separated).
+    #[arg(
+        long,
+        value_name = "PARTITIONS",
+        value_parser = parse_partitions,
+        env = "TURM_FILTER_PARTITION"
+    )]
+    filter_partition: Option<String>,
 }
 
 impl SqueueArgs {
+    /// Drops arguments from environment variables that conflict with flags,
+    /// see [`resolve_conflict`].
+    pub fn resolve_conflicts(&mut self, matches: &ArgMatches) -> Result<(), clap::Error> {
+        for (a, b) in CONFLICTS {
+            if self.is_set(a) && self.is_set(b) {
+                let dropped = resolve_conflict(matches, a, b)?;
+                self.unset(dropped);
+            }
+        }
+        Ok(())
+    }
+
+    fn is_set(&self, id: &str) -> bool {
+        match id {
+            "filter_user" => self.filter_user.is_some(),
+            "user" => self.user.is_some(),
+            "me" => self.me,
+            "all_users" => self.all_users,
+            "filter_partition" => self.filter_partition.is_some(),
+            "partition" => self.partition.is_some(),
+            "job" => self.job.is_some(),
+            _ => unreachable!("no conflicts for {}", id),
+        }
+    }
+
+    fn unset(&mut self, id: &str) {
+        match id {
+            "filter_user" => self.filter_user = None,
+            "user" => self.user = None,
+            "me" => self.me = false,
+            "all_users" => self.all_users = false,
+            "filter_partition" => self.filter_partition = None,
+            "partition" => self.partition = None,
+            "job" => self.job = None,
+            _ => unreachable!("no conflicts for {}", id),
+        }
+    }
+
+    pub fn has_job(&self) -> bool {
+        self.is_set("job")
+    }
+
+    pub fn clear_job(&mut self) {
+        self.unset("job");
+    }
+
+    /// Sets `--filter-user` unless the users were chosen on the command line.
+    pub fn set_default_filter_user(&mut self, user: Option<String>) {
+        if self.user.is_none() && !self.me && !self.all_users && self.filter_user.is_none() {
+            self.filter_user = user;
+        }
+    }
+
+    pub fn clusters(&self) -> Option<&str> {
+        self.clusters.as_deref()
+    }
+
     pub fn to_vec(&self) -> Vec<String> {
         let mut args = Vec::new();
         if let Some(account) = &self.account {
             args.push(format!("--account={}", account));
         }
-        if self.all {
+        if self.all || self.all_users {
             args.push("--all".to_string());
         }
         if self.federation {
@@ -146,6 +235,115 @@ impl SqueueArgs {
         if let Some(nodelist) = &self.nodelist {
             args.push(format!("--nodelist={}", nodelist));
         }
+        if let Some(user) = &self.filter_user {
+            let user = match user.as_str() {
+                "me" => current_user(),
+                _ => user.to_owned(),
+            };
+            args.push(format!("--user={}", user));
+        }
+        if let Some(partition) = &self.filter_partition {
+            args.push(format!("--partition={}", partition));
+        }
         args
     }
 }
+
+/// Of two conflicting arguments that are both set, returns the one to drop:
+/// the one from an environment variable if the other is a flag, like flags
+/// take precedence over environment variables everywhere else. An error if
+/// both are flags or both are environment variables.
+pub fn resolve_conflict<'a>(
+    matches: &ArgMatches,
+    a: &'a str,
+    b: &'a str,
+) -> Result<&'a str, clap::Error> {
+    let from_env = |id| matches.value_source(id) == Some(ValueSource::EnvVariable);
+    match (from_env(a), from_env(b)) {
+        (true, false) => Ok(a),
+        (false, true) => Ok(b),
+        _ => Err(clap::Error::raw(
+            ErrorKind::ArgumentConflict,
+            format!(
+                "the argument '--{}' cannot be used with '--{}'",
+                a.replace('_', "-"),
+                b.replace('_', "-")
+            ),
+        )),
+    }
+}
+
+/// Rejects partition lists that squeue would fail on with an obscure error.
+fn parse_partitions(s: &str) -> Result<String, String> {
+    if let Some(c) = s
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, ',' | '-' | '_' | '.')))
+    {
+        return Err(format!("partition names must not contain {:?}", c));
+    }
+    if s.split(',').any(|p| p.is_empty()) {
+        return Err("partition names must not be empty".to_string());
+    }
+    Ok(s.to_owned())
+}
+
+fn current_user() -> String {
+    env::var("USER").unwrap_or_else(|_| {
+        Command::new("whoami")
+            .output()
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_owned())
+            .unwrap_or_default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches, Parser};
+    use std::iter::once;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        squeue_args: SqueueArgs,
+    }
+
+    fn parse(args: &[&str]) -> Result<Vec<String>, clap::Error> {
+        let matches =
+            Cli::command().try_get_matches_from(once("turm").chain(args.iter().copied()))?;
+        let mut cli = Cli::from_arg_matches(&matches)?;
+        cli.squeue_args.resolve_conflicts(&matches)?;
+        Ok(cli.squeue_args.to_vec())
+    }
+
+    #[test]
+    fn test_env_conflicts() {
+        // the only test that sets TURM_* variables
+        env::set_var("TURM_FILTER_USER", "alice");
+        let flag_wins = parse(&["--user", "bob"]);
+        env::set_var("TURM_ME", "1");
+        let both_env = parse(&[]);
+        env::set_var("TURM_ME", "0");
+        let falsey = parse(&[]);
+        env::remove_var("TURM_FILTER_USER");
+        env::remove_var("TURM_ME");
+
+        assert_eq!(flag_wins.unwrap(), ["--user=bob"]);
+        assert!(both_env.is_err());
+        assert_eq!(falsey.unwrap(), ["--user=alice"]);
+        assert!(parse(&["--user", "bob", "--filter-user", "alice"]).is_err());
+        assert_eq!(parse(&["--me"]).unwrap(), ["--me"]);
+    }
+
+    #[test]
+    fn test_parse_partitions() {
+        assert_eq!(parse_partitions("gpu"), Ok("gpu".to_string()));
+        assert_eq!(
+            parse_partitions("gpu,gpu-large"),
+            Ok("gpu,gpu-large".to_string())
+        );
+        assert!(parse_partitions("gpu large").is_err());
+        assert!(parse_partitions("gpu;rm").is_err());
+        assert!(parse_partitions("gpu,").is_err());
+    }
+}
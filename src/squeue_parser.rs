@@ -0,0 +1,72 @@
+use std::io::BufRead;
+
+use crate::job::{output_separator, Job, JobParseError, SQUEUE_FIELDS};
+
+/// Turns the output of `squeue --Format` into jobs. The format names the
+/// [`SQUEUE_FIELDS`], each followed by a separator no job contains.
+pub struct SqueueParser {
+    separator: String,
+    /// Built once, the separator never changes.
+    format: String,
+}
+
+impl Default for SqueueParser {
+    fn default() -> Self {
+        Self::with_separator(output_separator())
+    }
+}
+
+impl SqueueParser {
+    pub fn with_separator(separator: String) -> Self {
+        let format = SQUEUE_FIELDS
+            .map(|s| s.to_owned() + ":" + &separator)
+            .join(",");
+        debug_assert_eq!(format.matches(&separator).count(), SQUEUE_FIELDS.len());
+        Self { separator, format }
+    }
+
+    /// The value of squeue's `--Format` flag, the same for every poll.
+    pub fn format(&self) -> &str {
+        &self.format
+    }
+
+    /// One result per line of jobs.
+    pub fn parse(&self, output: &[u8]) -> Vec<Result<Job, JobParseError>> {
+        output
+            .lines()
+            .map_while(Result::ok)
+            // `--clusters` puts a `CLUSTER: name` line before the jobs of each
+            // cluster, the jobs have their own cluster field
+            .filter(|line| !line.starts_with("CLUSTER: "))
+            .map(|line| Job::from_parts(&line, &self.separator))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job::test_squeue_line;
+
+    #[test]
+    fn test_parse() {
+        let parser = SqueueParser::with_separator("#".to_owned());
+        assert!(parser.format().starts_with("jobid:#,name:#,"));
+        assert_eq!(parser.format().split(',').count(), SQUEUE_FIELDS.len());
+
+        let line = test_squeue_line("#", &[("jobid", "1234")]);
+        let output = format!("CLUSTER: hpc1\n{}\n1235#train#\n", line);
+
+        let results = parser.parse(output.as_bytes());
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap().job_id, "1234");
+        assert_eq!(
+            results[1].as_ref().err(),
+            Some(&JobParseError::FieldCountMismatch {
+                expected: SQUEUE_FIELDS.len(),
+                got: 2
+            })
+        );
+        assert!(parser.parse(b"").is_empty());
+    }
+}
@@ -0,0 +1,42 @@
+use ratatui::style::Style;
+
+use crate::job::JobState;
+use crate::theme::Theme;
+
+/// The color of a job list row for a Slurm state such as `RUNNING`.
+pub fn state_style(theme: &Theme, state: &str) -> Style {
+    let state = JobState::new(state);
+    let color = if state.is_running() {
+        theme.running_fg
+    } else if state.is_pending() {
+        theme.pending_fg
+    } else if state.is_completing() {
+        theme.completing_fg
+    } else if state.is_error() {
+        theme.failed_fg
+    } else {
+        theme.other_fg
+    };
+    Style::default().fg(color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Color;
+
+    #[test]
+    fn test_state_style() {
+        let theme = Theme::default();
+        assert_eq!(state_style(&theme, "RUNNING").fg, Some(Color::Green));
+        assert_eq!(state_style(&theme, "PENDING").fg, Some(Color::Yellow));
+        assert_eq!(state_style(&theme, "COMPLETING").fg, Some(Color::Cyan));
+        assert_eq!(state_style(&theme, "TIMEOUT").fg, Some(Color::Red));
+        assert_eq!(state_style(&theme, "OUT_OF_MEMORY").fg, Some(Color::Red));
+        assert_eq!(
+            state_style(&theme, "CANCELLED by 1000").fg,
+            Some(Color::Gray)
+        );
+        assert_eq!(state_style(&theme, "").fg, Some(Color::Gray));
+    }
+}
@@ -0,0 +1,291 @@
+use std::env;
+
+use ratatui::style::{Color, Style};
+use serde::Deserialize;
+
+/// The `theme` setting of the config file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    #[default]
+    Default,
+    SolarizedDark,
+}
+
+impl ThemeName {
+    pub fn theme(&self) -> Theme {
+        match self {
+            ThemeName::Default => Theme::default(),
+            ThemeName::SolarizedDark => Theme::solarized_dark(),
+        }
+    }
+}
+
+/// The colors of the TUI.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+    pub running_fg: Color,
+    pub pending_fg: Color,
+    pub completing_fg: Color,
+    pub failed_fg: Color,
+    pub other_fg: Color,
+    pub selected_fg: Color,
+    pub selected_bg: Color,
+    /// Borders of the focused panel and of dialogs.
+    pub border_fg: Color,
+    /// Field names and table headers.
+    pub label_fg: Color,
+    pub text_fg: Color,
+    pub key_fg: Color,
+    pub key_description_fg: Color,
+    pub error_fg: Color,
+    pub success_fg: Color,
+    pub status_fg: Color,
+    pub status_bg: Color,
+    /// Progress and utilization bars, by how full they are.
+    pub usage_low_fg: Color,
+    pub usage_medium_fg: Color,
+    pub usage_high_fg: Color,
+}
+
+impl Default for Theme {
+    /// The terminal's own 16 colors, best on a dark background.
+    fn default() -> Self {
+        Self {
+            running_fg: Color::Green,
+            pending_fg: Color::Yellow,
+            completing_fg: Color::Cyan,
+            failed_fg: Color::Red,
+            other_fg: Color::Gray,
+            selected_fg: Color::Black,
+            selected_bg: Color::Green,
+            border_fg: Color::Green,
+            label_fg: Color::Yellow,
+            text_fg: Color::White,
+            key_fg: Color::Blue,
+            key_description_fg: Color::LightBlue,
+            error_fg: Color::Red,
+            success_fg: Color::Green,
+            status_fg: Color::White,
+            status_bg: Color::DarkGray,
+            usage_low_fg: Color::Green,
+            usage_medium_fg: Color::Yellow,
+            usage_high_fg: Color::Red,
+        }
+    }
+}
+
+impl Theme {
+    /// Ethan Schoonover's Solarized palette, see https://ethanschoonover.com/solarized/.
+    pub fn solarized_dark() -> Self {
+        const BASE03: Color = Color::Rgb(0x00, 0x2b, 0x36);
+        const BASE02: Color = Color::Rgb(0x07, 0x36, 0x42);
+        const BASE01: Color = Color::Rgb(0x58, 0x6e, 0x75);
+        const BASE1: Color = Color::Rgb(0x93, 0xa1, 0xa1);
+        const YELLOW: Color = Color::Rgb(0xb5, 0x89, 0x00);
+        const ORANGE: Color = Color::Rgb(0xcb, 0x4b, 0x16);
+        const RED: Color = Color::Rgb(0xdc, 0x32, 0x2f);
+        const VIOLET: Color = Color::Rgb(0x6c, 0x71, 0xc4);
+        const BLUE: Color = Color::Rgb(0x26, 0x8b, 0xd2);
+        const CYAN: Color = Color::Rgb(0x2a, 0xa1, 0x98);
+        const GREEN: Color = Color::Rgb(0x85, 0x99, 0x00);
+
+        Self {
+            running_fg: GREEN,
+            pending_fg: YELLOW,
+            completing_fg: CYAN,
+            failed_fg: RED,
+            other_fg: BASE01,
+            selected_fg: BASE03,
+            selected_bg: BLUE,
+            border_fg: BLUE,
+            label_fg: YELLOW,
+            text_fg: BASE1,
+            key_fg: VIOLET,
+            key_description_fg: BLUE,
+            error_fg: RED,
+            success_fg: GREEN,
+            status_fg: BASE1,
+            status_bg: BASE02,
+            usage_low_fg: GREEN,
+            usage_medium_fg: ORANGE,
+            usage_high_fg: RED,
+        }
+    }
+
+    /// Replaces the RGB colors by the closest ones the terminal can show.
+    pub fn with_depth(self, depth: ColorDepth) -> Self {
+        let convert = |color| match (depth, color) {
+            (ColorDepth::TrueColor, color) => color,
+            (ColorDepth::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(ansi256(r, g, b)),
+            (ColorDepth::Ansi16, Color::Rgb(r, g, b)) => ansi16(r, g, b),
+            (_, color) => color,
+        };
+        Self {
+            running_fg: convert(self.running_fg),
+            pending_fg: convert(self.pending_fg),
+            completing_fg: convert(self.completing_fg),
+            failed_fg: convert(self.failed_fg),
+            other_fg: convert(self.other_fg),
+            selected_fg: convert(self.selected_fg),
+            selected_bg: convert(self.selected_bg),
+            border_fg: convert(self.border_fg),
+            label_fg: convert(self.label_fg),
+            text_fg: convert(self.text_fg),
+            key_fg: convert(self.key_fg),
+            key_description_fg: convert(self.key_description_fg),
+            error_fg: convert(self.error_fg),
+            success_fg: convert(self.success_fg),
+            status_fg: convert(self.status_fg),
+            status_bg: convert(self.status_bg),
+            usage_low_fg: convert(self.usage_low_fg),
+            usage_medium_fg: convert(self.usage_medium_fg),
+            usage_high_fg: convert(self.usage_high_fg),
+        }
+    }
+
+    pub fn selected(&self) -> Style {
+        Style::default().bg(self.selected_bg).fg(self.selected_fg)
+    }
+
+    /// For a bar that is `fraction` full.
+    pub fn usage_color(&self, fraction: f64) -> Color {
+        if fraction < 0.75 {
+            self.usage_low_fg
+        } else if fraction <= 0.9 {
+            self.usage_medium_fg
+        } else {
+            self.usage_high_fg
+        }
+    }
+}
+
+/// How many colors the terminal supports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorDepth {
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+impl ColorDepth {
+    /// From `COLORTERM` and `TERM`, like most terminal applications.
+    pub fn detect() -> Self {
+        Self::from_env(
+            env::var("COLORTERM").ok().as_deref(),
+            env::var("TERM").ok().as_deref(),
+        )
+    }
+
+    fn from_env(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        if matches!(colorterm, Some("truecolor" | "24bit")) {
+            ColorDepth::TrueColor
+        } else if term.is_some_and(|t| t.contains("256color")) {
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    [(r1, r2), (g1, g2), (b1, b2)]
+        .into_iter()
+        .map(|(a, b)| (a.abs_diff(b) as u32).pow(2))
+        .sum()
+}
+
+/// The closest color of the xterm 256 color palette, from its 6x6x6 color
+/// cube or its 24 grays.
+fn ansi256(r: u8, g: u8, b: u8) -> u8 {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let level = |c: u8| {
+        (0..LEVELS.len())
+            .min_by_key(|&i| LEVELS[i].abs_diff(c))
+            .unwrap_or(0)
+    };
+    let (ri, gi, bi) = (level(r), level(g), level(b));
+    let cube = 16 + 36 * ri + 6 * gi + bi;
+    let cube_rgb = (LEVELS[ri], LEVELS[gi], LEVELS[bi]);
+
+    let gray_index = ((r as u32 + g as u32 + b as u32) / 3).saturating_sub(8) / 10;
+    let gray_index = gray_index.min(23) as u8;
+    let gray = 8 + 10 * gray_index;
+
+    if distance((gray, gray, gray), (r, g, b)) < distance(cube_rgb, (r, g, b)) {
+        232 + gray_index
+    } else {
+        cube as u8
+    }
+}
+
+/// The closest of the 16 basic colors, assuming xterm's default palette.
+fn ansi16(r: u8, g: u8, b: u8) -> Color {
+    const PALETTE: [(Color, (u8, u8, u8)); 16] = [
+        (Color::Black, (0, 0, 0)),
+        (Color::Red, (205, 0, 0)),
+        (Color::Green, (0, 205, 0)),
+        (Color::Yellow, (205, 205, 0)),
+        (Color::Blue, (0, 0, 238)),
+        (Color::Magenta, (205, 0, 205)),
+        (Color::Cyan, (0, 205, 205)),
+        (Color::Gray, (229, 229, 229)),
+        (Color::DarkGray, (127, 127, 127)),
+        (Color::LightRed, (255, 0, 0)),
+        (Color::LightGreen, (0, 255, 0)),
+        (Color::LightYellow, (255, 255, 0)),
+        (Color::LightBlue, (92, 92, 255)),
+        (Color::LightMagenta, (255, 0, 255)),
+        (Color::LightCyan, (0, 255, 255)),
+        (Color::White, (255, 255, 255)),
+    ];
+    PALETTE
+        .iter()
+        .min_by_key(|(_, rgb)| distance(*rgb, (r, g, b)))
+        .map_or(Color::Reset, |(color, _)| *color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_depth() {
+        assert_eq!(
+            ColorDepth::from_env(Some("truecolor"), Some("xterm-256color")),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            ColorDepth::from_env(None, Some("screen-256color")),
+            ColorDepth::Ansi256
+        );
+        assert_eq!(
+            ColorDepth::from_env(None, Some("xterm")),
+            ColorDepth::Ansi16
+        );
+        assert_eq!(ColorDepth::from_env(None, None), ColorDepth::Ansi16);
+    }
+
+    #[test]
+    fn test_with_depth() {
+        let theme = Theme::solarized_dark();
+        assert_eq!(
+            theme.clone().with_depth(ColorDepth::TrueColor),
+            Theme::solarized_dark()
+        );
+        // blue 268bd2
+        assert_eq!(
+            theme.clone().with_depth(ColorDepth::Ansi256).border_fg,
+            Color::Indexed(32)
+        );
+        assert_eq!(theme.with_depth(ColorDepth::Ansi16).failed_fg, Color::Red);
+        assert_eq!(
+            Theme::default().with_depth(ColorDepth::Ansi16),
+            Theme::default()
+        );
+
+        assert_eq!(ansi256(0, 0, 0), 16);
+        assert_eq!(ansi256(255, 255, 255), 231);
+        assert_eq!(ansi256(128, 128, 128), 244);
+    }
+}
@@ -0,0 +1,225 @@
+use serde::Serialize;
+
+/// Resources parsed from a Slurm TRES (trackable resources) string, e.g.
+/// `cpu=4,mem=16G,node=1,billing=4,gres/gpu=2`.
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct TresResources {
+    pub cpu: u32,
+    pub mem_mb: u64,
+    pub gpu: Option<GpuAlloc>,
+}
+
+/// GPUs of a job, in total over all its nodes.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct GpuAlloc {
+    pub count: u32,
+    /// e.g. `a100`, if all GPUs are of the same known type.
+    pub model: Option<String>,
+}
+
+impl TresResources {
+    pub fn parse(tres: &str) -> Option<Self> {
+        let tres = tres.trim();
+        if tres.is_empty() || tres == "N/A" {
+            return None;
+        }
+
+        let mut resources = TresResources::default();
+        let mut nodes = 1;
+        let mut gpu_untyped = None;
+        let mut gpu_typed = None;
+        let mut gpu_per_node = None;
+        let mut models = Vec::new();
+
+        for item in tres.split(',') {
+            match item.split_once('=') {
+                Some(("cpu", v)) => resources.cpu = v.parse().ok()?,
+                Some(("mem", v)) => resources.mem_mb = parse_mem_mb(v)?,
+                Some(("node", v)) => nodes = v.parse().ok()?,
+                Some(("gres/gpu", v)) => gpu_untyped = Some(v.parse().ok()?),
+                // typed allocation, e.g. `gres/gpu:a100=1`
+                Some((k, v)) if k.starts_with("gres/gpu:") => {
+                    let count = v.parse::<u32>().ok()?;
+                    gpu_typed = Some(gpu_typed.unwrap_or(0u32).checked_add(count)?);
+                    models.push(&k["gres/gpu:".len()..]);
+                }
+                Some(_) => {}
+                // `tres-per-node` request, e.g. `gres/gpu:a100:2` or `gres:gpu:2`
+                None => {
+                    if let Some(spec) = item
+                        .strip_prefix("gres/gpu")
+                        .or_else(|| item.strip_prefix("gres:gpu"))
+                    {
+                        let (count, model) = parse_gpu_spec(spec);
+                        gpu_per_node = Some(gpu_per_node.unwrap_or(0u32).checked_add(count)?);
+                        models.extend(model);
+                    }
+                }
+            }
+        }
+
+        let count = match (gpu_untyped.or(gpu_typed), gpu_per_node) {
+            (Some(count), _) => Some(count),
+            (None, Some(per_node)) => Some(per_node.checked_mul(nodes)?),
+            (None, None) => None,
+        };
+        models.sort_unstable();
+        models.dedup();
+        resources.gpu = count.map(|count| GpuAlloc {
+            count,
+            model: match models[..] {
+                [model] => Some(model.to_owned()),
+                _ => None,
+            },
+        });
+        Some(resources)
+    }
+
+    /// `None` if no memory is allocated.
+    pub fn mem_bytes(&self) -> Option<u64> {
+        (self.mem_mb > 0).then(|| self.mem_mb.saturating_mul(1024 * 1024))
+    }
+
+    pub fn mem_human(&self) -> Option<String> {
+        Some(format_mem_mb(self.mem_bytes()? / (1024 * 1024)))
+    }
+}
+
+/// `(count, model)` of a GPU request like `:a100:2`, `:2`, `:a100` or empty.
+fn parse_gpu_spec(spec: &str) -> (u32, Option<&str>) {
+    let mut count = 1;
+    let mut model = None;
+    for part in spec.split(':').filter(|p| !p.is_empty()) {
+        match part.parse() {
+            Ok(n) => count = n,
+            Err(_) => model = Some(part),
+        }
+    }
+    (count, model)
+}
+
+/// In GiB if it is at least 1 GiB, in MiB otherwise.
+pub fn format_mem_mb(mb: u64) -> String {
+    if mb < 1024 {
+        format!("{} MiB", mb)
+    } else if mb.is_multiple_of(1024) {
+        format!("{} GiB", mb / 1024)
+    } else {
+        format!("{:.1} GiB", mb as f64 / 1024.0)
+    }
+}
+
+/// Parses a Slurm memory value (e.g. `16G`, `16384M`, `1.50T`, or `16GB` in
+/// some Slurm versions) into MB. Values without a unit are already in MB.
+fn parse_mem_mb(s: &str) -> Option<u64> {
+    let s = s.strip_suffix('B').unwrap_or(s);
+    let (number, factor) = match s.char_indices().last()? {
+        (i, 'K') => (&s[..i], 1.0 / 1024.0),
+        (i, 'M') => (&s[..i], 1.0),
+        (i, 'G') => (&s[..i], 1024.0),
+        (i, 'T') => (&s[..i], 1024.0 * 1024.0),
+        (i, 'P') => (&s[..i], 1024.0 * 1024.0 * 1024.0),
+        _ => (s, 1.0),
+    };
+    let number = number.parse::<f64>().ok()?;
+    Some((number * factor).round() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gpu(tres: &str) -> Option<(u32, Option<String>)> {
+        TresResources::parse(tres)
+            .unwrap()
+            .gpu
+            .map(|g| (g.count, g.model))
+    }
+
+    #[test]
+    fn test_parse_tres() {
+        assert_eq!(
+            TresResources::parse("cpu=4,mem=16G,node=1,billing=4,gres/gpu=2"),
+            Some(TresResources {
+                cpu: 4,
+                mem_mb: 16 * 1024,
+                gpu: Some(GpuAlloc {
+                    count: 2,
+                    model: None
+                }),
+            })
+        );
+
+        // No GPU allocation
+        assert_eq!(
+            TresResources::parse("cpu=1,mem=500M,node=1,billing=1"),
+            Some(TresResources {
+                cpu: 1,
+                mem_mb: 500,
+                gpu: None,
+            })
+        );
+
+        // Typed GPUs, without and with the untyped total
+        assert_eq!(
+            TresResources::parse("cpu=8,mem=1.50T,gres/gpu:a100=1,gres/gpu:v100=2"),
+            Some(TresResources {
+                cpu: 8,
+                mem_mb: 1536 * 1024,
+                gpu: Some(GpuAlloc {
+                    count: 3,
+                    model: None
+                }),
+            })
+        );
+        assert_eq!(
+            gpu("cpu=8,mem=64G,gres/gpu=1,gres/gpu:a100=1"),
+            Some((1, Some("a100".into())))
+        );
+
+        // `tres-per-node` appended to `tres-alloc`
+        assert_eq!(
+            gpu("cpu=2,mem=8G,node=1,gres/gpu:a100:2"),
+            Some((2, Some("a100".into())))
+        );
+        assert_eq!(gpu("cpu=2,mem=8G,gres:gpu:1"), Some((1, None)));
+        assert_eq!(gpu("cpu=2,mem=8G,gres/gpu"), Some((1, None)));
+        // per node, times the number of nodes
+        assert_eq!(
+            gpu("cpu=16,mem=64G,node=4,gres/gpu:h100:8"),
+            Some((32, Some("h100".into())))
+        );
+        assert_eq!(gpu("cpu=2,node=2,gres/gpu:2"), Some((4, None)));
+
+        // `MB`/`GB` suffixes
+        assert_eq!(
+            TresResources::parse("cpu=1,mem=16GB").unwrap().mem_mb,
+            16 * 1024
+        );
+        assert_eq!(TresResources::parse("cpu=1,mem=500MB").unwrap().mem_mb, 500);
+
+        assert_eq!(TresResources::parse("N/A"), None);
+        assert_eq!(TresResources::parse(""), None);
+        assert_eq!(TresResources::parse("cpu=x"), None);
+        // u32 overflow
+        assert_eq!(
+            TresResources::parse("cpu=2,node=4294967295,gres/gpu:2"),
+            None
+        );
+        assert_eq!(
+            TresResources::parse("gres/gpu:a100=4294967295,gres/gpu:v100=1"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_mem_human() {
+        let mem = |tres| TresResources::parse(tres).unwrap();
+        assert_eq!(mem("mem=16384M").mem_human().as_deref(), Some("16 GiB"));
+        assert_eq!(mem("mem=32G").mem_bytes(), Some(32 << 30));
+        assert_eq!(mem("mem=1T").mem_human().as_deref(), Some("1024 GiB"));
+        assert_eq!(mem("mem=1536M").mem_human().as_deref(), Some("1.5 GiB"));
+        assert_eq!(mem("mem=500MB").mem_human().as_deref(), Some("500 MiB"));
+        assert_eq!(mem("cpu=4").mem_human(), None);
+    }
+}
@@ -0,0 +1,204 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    sync::Once,
+    time::Duration,
+};
+
+use crossbeam::channel::{unbounded, Receiver};
+use turm::app::AppMessage;
+use turm::diff::{apply_changes, JobChange};
+use turm::job::Job;
+use turm::job_watcher::{JobWatcherConfig, JobWatcherHandle};
+
+/// Starts the job watcher with `bin/squeue` and `bin/sacct` first in `$PATH`.
+fn mock_watcher(config: JobWatcherConfig) -> (JobWatcherHandle, Receiver<AppMessage>) {
+    static MOCK_PATH: Once = Once::new();
+    MOCK_PATH.call_once(|| {
+        let bin = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/integration/bin");
+        let path = env::var_os("PATH").unwrap_or_default();
+        let paths = [bin].into_iter().chain(env::split_paths(&path));
+        env::set_var("PATH", env::join_paths(paths).unwrap());
+    });
+
+    let (sender, receiver) = unbounded();
+    (JobWatcherHandle::new(sender, config), receiver)
+}
+
+/// The messages of the watcher up to and including the next job list, or
+/// what changed about it.
+fn next_poll(receiver: &Receiver<AppMessage>) -> Vec<AppMessage> {
+    let mut messages = Vec::new();
+    loop {
+        match receiver.recv_timeout(Duration::from_secs(10)).unwrap() {
+            AppMessage::SqueueError(e) | AppMessage::WatcherDied(e) => panic!("{}", e),
+            AppMessage::JobParseErrors(errors) => panic!("{}", errors[0]),
+            message @ (AppMessage::Jobs(_) | AppMessage::JobsChanged(_)) => {
+                messages.push(message);
+                return messages;
+            }
+            message => messages.push(message),
+        }
+    }
+}
+
+/// The jobs of the first poll.
+fn jobs_from_mock_squeue(config: JobWatcherConfig) -> Vec<Job> {
+    let (mut watcher, receiver) = mock_watcher(config);
+    let jobs = match next_poll(&receiver).pop() {
+        Some(AppMessage::Jobs(jobs)) => jobs,
+        _ => unreachable!(),
+    };
+    watcher.shutdown();
+    jobs
+}
+
+#[test]
+fn test_job_watcher_with_mock_squeue() {
+    let jobs = jobs_from_mock_squeue(JobWatcherConfig::default());
+    assert_eq!(jobs.len(), 2);
+
+    let running = &jobs[0];
+    assert_eq!(running.id(), "1001");
+    assert_eq!(running.name, "train");
+    assert!(running.is_running());
+    assert_eq!(running.user, "alice");
+    assert_eq!(running.partition, "gpu");
+    assert_eq!(
+        running.tres,
+        "cpu=8,mem=32G,node=1,billing=8,gres/gpu=2,gres/gpu:a100:2"
+    );
+    assert_eq!(running.gpu_count(), Some(2));
+    assert_eq!(running.elapsed_seconds(), Some(3723));
+    assert_eq!(running.time_limit_seconds(), Some(86400));
+    assert_eq!(running.priority, Some(4294));
+    assert_eq!(running.account, "proj1");
+    assert_eq!(running.cluster.as_deref(), Some("hpc1"));
+    assert_eq!(running.submit_node.as_deref(), Some("node01"));
+    assert_eq!(running.reservation, None);
+    assert_eq!(running.nodes(), ["node01"]);
+    assert_eq!(
+        running.stdout,
+        Some(PathBuf::from("/home/alice/slurm-1001.out"))
+    );
+
+    let pending = &jobs[1];
+    assert_eq!(pending.id(), "1003_2");
+    assert_eq!(pending.job_id, "1005");
+    assert_eq!(pending.name, "sweep lr=0.1");
+    assert!(pending.is_pending());
+    assert_eq!(pending.time_limit.as_deref(), Some("UNLIMITED"));
+    assert_eq!(pending.start_time, None);
+    assert_eq!(pending.qos, "debug");
+    assert_eq!(pending.reservation.as_deref(), Some("maint"));
+    assert_eq!(pending.dependencies()[0].job_ids, ["1001"]);
+    assert_eq!(pending.submit_node, None);
+    assert_eq!(
+        pending.stderr,
+        Some(PathBuf::from("/home/bob/sweep lr=0.1-1003_2.out"))
+    );
+}
+
+#[test]
+fn test_watch_file_with_unknown_jobs() {
+    let file = env::temp_dir().join(format!("turm-watch-{}", std::process::id()));
+    let watch = |ids: &str| {
+        fs::write(&file, ids).unwrap();
+        // a single failure would end the watcher
+        let config = JobWatcherConfig::default()
+            .watch_file(Some(file.clone()))
+            .max_retries(1);
+        jobs_from_mock_squeue(config)
+    };
+
+    let ids = |jobs: Vec<Job>| jobs.iter().map(Job::id).collect::<Vec<_>>();
+    assert_eq!(ids(watch("1001\n1003_2\n")), ["1001", "1003_2"]);
+    // squeue fails for the unknown job, the others are still reported
+    assert_eq!(ids(watch("Submitted batch job 999\n1003_2\n")), ["1003_2"]);
+    assert!(watch("999\n").is_empty());
+    fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn test_unknown_jobs_are_no_failure() {
+    let config = JobWatcherConfig::default()
+        .squeue_args(vec!["--jobs=999".to_owned()])
+        .max_retries(1);
+    assert!(jobs_from_mock_squeue(config).is_empty());
+}
+
+#[test]
+fn test_finished_job_is_a_state_change() {
+    let file = env::temp_dir().join(format!("turm-finished-{}", std::process::id()));
+    fs::write(&file, "1001\n1003_2\n").unwrap();
+    let config = JobWatcherConfig::default()
+        .interval(Duration::from_secs(3600))
+        .watch_file(Some(file.clone()));
+
+    // without sacct the final state is unknown
+    let (mut watcher, receiver) = mock_watcher(config.clone());
+    assert_eq!(next_poll(&receiver).len(), 1);
+    fs::write(&file, "1003_2\n").unwrap();
+    watcher.poll_now();
+    assert!(matches!(
+        &next_poll(&receiver)[..],
+        [AppMessage::JobsChanged(_)]
+    ));
+    watcher.shutdown();
+
+    fs::write(&file, "1001\n1003_2\n").unwrap();
+    let (mut watcher, receiver) =
+        mock_watcher(config.sacct_enabled(Some(Duration::from_secs(3600))));
+    assert_eq!(next_poll(&receiver).len(), 1);
+
+    // the running job left the queue, sacct knows it completed
+    fs::write(&file, "1003_2\n").unwrap();
+    watcher.poll_now();
+    let [AppMessage::JobsChanged(changes)] = &next_poll(&receiver)[..] else {
+        panic!("no changes");
+    };
+    assert!(matches!(&changes[..], [JobChange::Removed(job)] if job.id() == "1001"));
+    let Ok(AppMessage::JobStateChanged {
+        id,
+        old_state,
+        new_state,
+    }) = receiver.recv_timeout(Duration::from_secs(10))
+    else {
+        panic!("no state change");
+    };
+    assert_eq!(id, "1001");
+    assert_eq!(old_state, "RUNNING");
+    assert_eq!(new_state, "COMPLETED");
+    watcher.shutdown();
+    fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn test_only_changes_are_sent() {
+    let file = env::temp_dir().join(format!("turm-changes-{}", std::process::id()));
+    fs::write(&file, "1001\n").unwrap();
+    let config = JobWatcherConfig::default()
+        .interval(Duration::from_secs(3600))
+        .watch_file(Some(file.clone()));
+    let (mut watcher, receiver) = mock_watcher(config);
+    let Some(AppMessage::Jobs(mut jobs)) = next_poll(&receiver).pop() else {
+        panic!("no jobs");
+    };
+    // nothing changed
+    watcher.poll_now();
+    assert!(matches!(&next_poll(&receiver)[..], [AppMessage::JobsChanged(c)] if c.is_empty()));
+
+    fs::write(&file, "1001\n1003_2\n").unwrap();
+    watcher.poll_now();
+    let Some(AppMessage::JobsChanged(changes)) = next_poll(&receiver).pop() else {
+        panic!("no changes");
+    };
+    assert!(matches!(&changes[..], [JobChange::Added(job)] if job.id() == "1003_2"));
+    apply_changes(&mut jobs, changes);
+    assert_eq!(
+        jobs.iter().map(Job::id).collect::<Vec<_>>(),
+        ["1001", "1003_2"]
+    );
+    watcher.shutdown();
+    fs::remove_file(&file).unwrap();
+}
@@ -0,0 +1,92 @@
+use proptest::prelude::*;
+use turm::job::{Job, JobParseError, SQUEUE_FIELDS};
+
+/// Like one of [`turm::job::output_separator`].
+const SEPARATOR: &str = "###turm-0123456789abcdef###";
+
+/// A squeue line with `fields` in the order of [`SQUEUE_FIELDS`].
+fn line(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|f| format!("{}{}", f, SEPARATOR))
+        .collect()
+}
+
+/// Any text, including empty and very long values and pieces of the
+/// separator, but never the whole separator.
+fn value() -> impl Strategy<Value = String> {
+    prop_oneof![
+        2 => Just(String::new()),
+        4 => ".{0,20}",
+        1 => ".{500,2000}",
+        2 => "[#turm-]{0,20}",
+    ]
+    .prop_filter("contains the separator", |v| !v.contains(SEPARATOR))
+}
+
+fn fields() -> impl Strategy<Value = Vec<String>> {
+    proptest::collection::vec(value(), SQUEUE_FIELDS.len()).prop_map(|mut fields| {
+        // required, and whitespace at the start of the line is trimmed
+        fields[0] = format!("1{}", fields[0]);
+        fields[2] = format!("R{}", fields[2]);
+        fields[3] = format!("u{}", fields[3]);
+        fields
+    })
+}
+
+proptest! {
+    // a line has 28 fields, generating them is slow in debug builds
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn test_from_parts_keeps_values(fields in fields()) {
+        let job = Job::from_parts(&line(&fields), SEPARATOR).unwrap();
+        prop_assert_eq!(&job.job_id, &fields[0]);
+        prop_assert_eq!(&job.name, &fields[1]);
+        prop_assert_eq!(&job.state, &fields[2]);
+        prop_assert_eq!(&job.user, &fields[3]);
+        prop_assert_eq!(&job.partition, &fields[6]);
+        prop_assert_eq!(&job.command, &fields[10]);
+        prop_assert_eq!(&job.qos, &fields[22]);
+        prop_assert_eq!(&job.account, &fields[26]);
+    }
+
+    #[test]
+    fn test_from_parts_separator_in_value(
+        fields in fields(),
+        index in 0..SQUEUE_FIELDS.len(),
+    ) {
+        let mut fields = fields;
+        fields[index] = format!("a{}b", SEPARATOR);
+        prop_assert_eq!(
+            Job::from_parts(&line(&fields), SEPARATOR).err(),
+            Some(JobParseError::FieldCountMismatch {
+                expected: SQUEUE_FIELDS.len(),
+                got: SQUEUE_FIELDS.len() + 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_parts_missing_field(
+        fields in fields(),
+        (index, name) in prop_oneof![
+            Just((0, "jobid")),
+            Just((2, "state")),
+            Just((3, "username")),
+        ],
+    ) {
+        let mut fields = fields;
+        fields[index] = String::new();
+        prop_assert_eq!(
+            Job::from_parts(&line(&fields), SEPARATOR).err(),
+            Some(JobParseError::MissingField(name))
+        );
+    }
+
+    #[test]
+    fn test_from_parts_does_not_panic(line in ".{0,3000}") {
+        let _ = Job::from_parts(&line, "#");
+        let _ = Job::from_parts(&line, SEPARATOR);
+    }
+}